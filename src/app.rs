@@ -12,7 +12,10 @@
  */
 use super::MAX_WRAP;
 use crate::about_panel::AboutPanel;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::distance_table::DistanceTable;
 use crate::gallery_panel::GalleryPanel;
+use crate::npuzzle::NBoard;
 use crate::puzzle_panel::PuzzlePanel;
 use crate::settings_panel::SettingsPanel;
 use egui_extras::RetainedImage;
@@ -104,6 +107,9 @@ impl NPuzzle {
         // This is also where you can customize the look and feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
 
+        #[cfg(not(target_arch = "wasm32"))]
+        DistanceTable::install_cached();
+
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
         if let Some(storage) = cc.storage {
@@ -277,7 +283,8 @@ impl eframe::App for NPuzzle {
 
         match cmd {
             Command::ResetGrid => {
-                self.puzzle_panel.set_mn(self.settings_panel.get_mn());
+                let (m, n) = self.settings_panel.get_mn();
+                self.puzzle_panel.set_mn(m, n);
             }
             _ => {}
         }
@@ -425,6 +432,10 @@ impl NPuzzle {
             .set_puzzle_panel_constrained_width(self.puzzle_panel.get_constrained_width());
         self.settings_panel
             .set_puzzle_subimage_count(self.puzzle_panel.get_puzzle_subimage_count());
+        self.settings_panel
+            .set_last_search(self.puzzle_panel.get_last_search());
+        self.settings_panel
+            .set_compared_search(self.puzzle_panel.get_last_comparison());
         self.settings_panel
             .set_puzzle_retained_image_count(self.puzzle_panel.get_puzzle_retained_image_count());
         self.settings_panel
@@ -442,6 +453,69 @@ impl NPuzzle {
         }
         self.puzzle_panel
             .set_game_mode(self.settings_panel.get_game_mode());
+        self.puzzle_panel
+            .set_difficulty(self.settings_panel.get_difficulty());
+        self.puzzle_panel.set_seed(self.settings_panel.get_seed());
+        self.puzzle_panel.set_goal(self.settings_panel.get_goal());
+        self.settings_panel
+            .set_last_seed(self.puzzle_panel.get_last_seed());
+        self.settings_panel.set_agent_options(
+            self.puzzle_panel.get_solver_names(),
+            self.puzzle_panel.get_heuristic_names(),
+        );
+        self.settings_panel.set_heuristic_enabled(
+            self.puzzle_panel
+                .solver_uses_heuristic(&self.settings_panel.get_solver()),
+        );
+        self.settings_panel.set_weight_enabled(
+            self.puzzle_panel
+                .solver_uses_weight(&self.settings_panel.get_solver()),
+        );
+        self.settings_panel.set_time_budget_enabled(
+            self.puzzle_panel
+                .solver_uses_time_budget(&self.settings_panel.get_solver()),
+        );
+        self.settings_panel.set_depth_limit_enabled(
+            self.puzzle_panel
+                .solver_uses_depth_limit(&self.settings_panel.get_solver()),
+        );
+        self.settings_panel.set_prune_duplicates_enabled(
+            self.puzzle_panel
+                .solver_uses_move_pruning(&self.settings_panel.get_solver()),
+        );
+        self.puzzle_panel.set_agent_parameters(
+            self.settings_panel.get_weight(),
+            self.settings_panel.get_time_budget_ms(),
+            self.settings_panel.get_depth_limit(),
+            self.settings_panel.get_prune_duplicates(),
+        );
+        self.puzzle_panel.set_agent(
+            self.settings_panel.get_solver(),
+            self.settings_panel.get_heuristic(),
+        );
+        if let Some(text) = self.settings_panel.take_puzzle_text() {
+            let loaded = NBoard::from_text(&text, &self.settings_panel.get_goal())
+                .and_then(|board| self.puzzle_panel.load_board(board));
+            self.settings_panel
+                .set_puzzle_file_status(Some(match loaded {
+                    Ok(()) => "Puzzle loaded".to_owned(),
+                    Err(e) => e,
+                }));
+        }
+        if self.settings_panel.take_save_request() {
+            match self.puzzle_panel.get_board_text() {
+                Ok(text) => self.settings_panel.save_puzzle_text(text),
+                Err(e) => self.settings_panel.set_puzzle_file_status(Some(e)),
+            }
+        }
+        if let Some(moves) = self.settings_panel.take_moves() {
+            let applied = self.puzzle_panel.apply_moves(&moves);
+            self.settings_panel
+                .set_puzzle_file_status(Some(match applied {
+                    Ok(()) => format!("Applied {} moves", moves.len()),
+                    Err(e) => e,
+                }));
+        }
     }
 
     fn calc_top_panel_button_rects(&mut self, ui: &egui::Ui) {
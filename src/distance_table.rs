@@ -0,0 +1,469 @@
+/**
+ * @file distance_table.rs
+ *
+ * @brief This is the module that enumerates every state of a small board with a breadth-first search
+ * from the goal, and records how many moves each one is from being solved.
+ *
+ * States are perfectly hashed by the rank of their tile permutation, so a table is just one byte per
+ * permutation. The half of the permutations that can never be reached from the goal stay UNSEEN.
+ *
+ * Tables are built for the row major goal. A board with any other goal is looked up by the goal cell
+ * of each of its tiles instead, which turns it into the equivalent row major board.
+ *
+ * Building the 3x3 tables takes a moment, so natively each one is saved to the temp directory after
+ * it is first built, and every saved table is installed again when the app starts. On the web that
+ * moment would freeze a frame, so tables are only ever built there a TableBuilder step at a time,
+ * and lookups made from the ui only use tables that are ready.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use crate::npuzzle::NBoard;
+use crate::search::Build;
+use log::{debug, error};
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// 9 tiles is 9! = 362880 permutations; a 10th would make the table ten times larger and slower to
+// build than we'd like on the web.
+const MAX_CELLS: usize = 9;
+const UNSEEN: u8 = u8::MAX;
+// Frontier states a TableBuilder expands per step.
+const BUILD_STATES: usize = 64;
+const MAGIC: &[u8; 4] = b"NPDT";
+const VERSION: u8 = 1;
+
+type TableKey = (usize, usize, usize);
+
+static TABLES: Mutex<Option<HashMap<TableKey, Arc<DistanceTable>>>> = Mutex::new(None);
+
+pub struct DistanceTable {
+    rows: usize,
+    cols: usize,
+    blank_tile: usize,
+    distances: Vec<u8>,
+    max_depth: usize,
+}
+
+impl DistanceTable {
+    pub fn supports(rows: usize, cols: usize) -> bool {
+        rows * cols <= MAX_CELLS
+    }
+
+    // One table per board shape and missing tile, built the first time it's asked for.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn get(rows: usize, cols: usize, blank_tile: usize) -> Arc<Self> {
+        let mut tables = TABLES.lock().unwrap();
+        tables
+            .get_or_insert_with(HashMap::new)
+            .entry((rows, cols, blank_tile))
+            .or_insert_with(|| {
+                let table = Self::build(rows, cols, blank_tile);
+                table.save_cached();
+                Arc::new(table)
+            })
+            .clone()
+    }
+
+    // The table, if it can be had without holding up the web's frame: natively it is built on the
+    // spot, on the web only a table that is already built will do.
+    pub fn ready(rows: usize, cols: usize, blank_tile: usize) -> Option<Arc<Self>> {
+        if !Self::supports(rows, cols) {
+            return None;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Some(Self::get(rows, cols, blank_tile))
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            Self::cached(rows, cols, blank_tile)
+        }
+    }
+
+    pub fn cached(rows: usize, cols: usize, blank_tile: usize) -> Option<Arc<Self>> {
+        TABLES
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|tables| tables.get(&(rows, cols, blank_tile)).cloned())
+    }
+
+    // None once the table is built, or for boards too large to have one.
+    pub fn builder(rows: usize, cols: usize, blank_tile: usize) -> Option<TableBuilder> {
+        if !Self::supports(rows, cols) || Self::cached(rows, cols, blank_tile).is_some() {
+            return None;
+        }
+        Some(TableBuilder::new(rows, cols, blank_tile))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_cached(&self) {
+        let path = Self::cache_dir().join(format!(
+            "{}x{}_{}.npdt",
+            self.rows, self.cols, self.blank_tile
+        ));
+        let saved = std::fs::create_dir_all(Self::cache_dir()).and_then(|_| self.save(&path));
+        if let Err(e) = saved {
+            error!("could not save {}: {}", path.display(), e);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn save_cached(&self) {}
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn cache_dir() -> std::path::PathBuf {
+        std::env::temp_dir().join("npuzzle")
+    }
+
+    // Installs every table an earlier run saved, so none of them has to be built again.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn install_cached() {
+        let Ok(entries) = std::fs::read_dir(Self::cache_dir()) else {
+            return;
+        };
+        let paths = entries.flatten().map(|entry| entry.path()).filter(|path| {
+            path.extension()
+                .map_or(false, |extension| extension == "npdt")
+        });
+        for path in paths {
+            match Self::load(&path) {
+                Ok(table) => {
+                    Self::install(table);
+                }
+                Err(e) => error!("could not load {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    // Hands a loaded table to get, so it is never built.
+    pub fn install(table: Self) -> Arc<Self> {
+        let table = Arc::new(table);
+        TABLES
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .insert((table.rows, table.cols, table.blank_tile), table.clone());
+        table
+    }
+
+    pub fn build(rows: usize, cols: usize, blank_tile: usize) -> Self {
+        let mut builder = TableBuilder::new(rows, cols, blank_tile);
+        while !builder.step(usize::MAX) {}
+        builder.finish()
+    }
+
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::default();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.rows as u8);
+        bytes.push(self.cols as u8);
+        bytes.push(self.blank_tile as u8);
+        bytes.extend_from_slice(&(self.distances.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.distances);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let header = MAGIC.len() + 8;
+        if bytes.len() < header || &bytes[..MAGIC.len()] != MAGIC {
+            return Err("not a distance table file".to_owned());
+        }
+        let version = bytes[MAGIC.len()];
+        if version != VERSION {
+            return Err(format!("unsupported distance table version {}", version));
+        }
+
+        let rows = bytes[MAGIC.len() + 1] as usize;
+        let cols = bytes[MAGIC.len() + 2] as usize;
+        let blank_tile = bytes[MAGIC.len() + 3] as usize;
+        if !Self::supports(rows, cols) || blank_tile >= rows * cols {
+            return Err(format!(
+                "a {}x{} table with blank tile {} is not supported",
+                rows, cols, blank_tile
+            ));
+        }
+        let len = u32::from_le_bytes(bytes[MAGIC.len() + 4..header].try_into().unwrap()) as usize;
+        if len != factorial(rows * cols) || bytes.len() != header + len {
+            return Err(format!(
+                "a {}x{} table should hold {} entries, found {}",
+                rows,
+                cols,
+                factorial(rows * cols),
+                bytes.len() - header
+            ));
+        }
+
+        let distances = bytes[header..].to_vec();
+        let max_depth = distances
+            .iter()
+            .filter(|d| **d != UNSEEN)
+            .max()
+            .copied()
+            .unwrap_or(0) as usize;
+        Ok(Self {
+            rows,
+            cols,
+            blank_tile,
+            distances,
+            max_depth,
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        Self::from_bytes(&bytes)
+    }
+
+    // None for boards of another shape or missing tile, boards without a blank yet, and boards
+    // that can't be solved.
+    pub fn distance(&self, board: &NBoard) -> Option<usize> {
+        if board.get_m() != self.rows
+            || board.get_n() != self.cols
+            || board.blank_goal() != Some(self.blank_tile)
+        {
+            return None;
+        }
+        let tiles: Vec<usize> = (0..self.rows * self.cols)
+            .map(|cell| board.goal_cell(board.index_at(cell)))
+            .collect();
+        match self.distances[rank(&tiles)] {
+            UNSEEN => None,
+            distance => Some(distance as usize),
+        }
+    }
+
+    // Picks uniformly among every state between `min` and `max` moves from the goal, returned as
+    // the (row major) tile sitting in each cell.
+    pub fn random_at_depth<R: Rng>(
+        &self,
+        min: usize,
+        max: usize,
+        rng: &mut R,
+    ) -> Option<Vec<usize>> {
+        let in_range =
+            |distance: &u8| *distance != UNSEEN && (min..=max).contains(&(*distance as usize));
+        let count = self.distances.iter().filter(|d| in_range(d)).count();
+        if count == 0 {
+            return None;
+        }
+
+        let pick = rng.gen_range(0..count);
+        let state = self
+            .distances
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| in_range(d))
+            .nth(pick)
+            .map(|(state, _)| state)?;
+
+        let mut tiles = vec![0; self.rows * self.cols];
+        unrank(state, &mut tiles);
+        Some(tiles)
+    }
+}
+
+// Breadth-first search from the goal, a few frontier states at a time.
+pub struct TableBuilder {
+    rows: usize,
+    cols: usize,
+    blank_tile: usize,
+    distances: Vec<u8>,
+    frontier: Vec<usize>,
+    next_frontier: Vec<usize>,
+    // how far through the frontier the search is
+    position: usize,
+    depth: u8,
+    tiles: Vec<usize>,
+}
+
+impl TableBuilder {
+    fn new(rows: usize, cols: usize, blank_tile: usize) -> Self {
+        let cells = rows * cols;
+        let goal: Vec<usize> = (0..cells).collect();
+        let mut distances = vec![UNSEEN; factorial(cells)];
+        distances[rank(&goal)] = 0;
+        Self {
+            rows,
+            cols,
+            blank_tile,
+            distances,
+            frontier: vec![rank(&goal)],
+            next_frontier: Vec::default(),
+            position: 0,
+            depth: 0,
+            tiles: goal,
+        }
+    }
+
+    // Expands up to `states` frontier states, returning true once every state has been reached.
+    fn step(&mut self, states: usize) -> bool {
+        let end = self
+            .frontier
+            .len()
+            .min(self.position.saturating_add(states));
+        for state in &self.frontier[self.position..end] {
+            unrank(*state, &mut self.tiles);
+            let blank = self
+                .tiles
+                .iter()
+                .position(|t| *t == self.blank_tile)
+                .unwrap();
+            for neighbour in neighbours(blank, self.rows, self.cols)
+                .into_iter()
+                .flatten()
+            {
+                self.tiles.swap(blank, neighbour);
+                let next = rank(&self.tiles);
+                if self.distances[next] == UNSEEN {
+                    self.distances[next] = self.depth + 1;
+                    self.next_frontier.push(next);
+                }
+                self.tiles.swap(blank, neighbour);
+            }
+        }
+        self.position = end;
+        if self.position < self.frontier.len() {
+            return false;
+        }
+
+        if !self.next_frontier.is_empty() {
+            self.depth += 1;
+        }
+        self.frontier = std::mem::take(&mut self.next_frontier);
+        self.position = 0;
+        self.frontier.is_empty()
+    }
+
+    fn finish(&mut self) -> DistanceTable {
+        debug!(
+            "distance table: {}x{} (blank tile {}) is at most {} moves deep",
+            self.rows, self.cols, self.blank_tile, self.depth
+        );
+        DistanceTable {
+            rows: self.rows,
+            cols: self.cols,
+            blank_tile: self.blank_tile,
+            distances: std::mem::take(&mut self.distances),
+            max_depth: self.depth as usize,
+        }
+    }
+}
+
+// Installs the table once it is built, saving it too where there is somewhere to save it.
+impl Build for TableBuilder {
+    fn build(&mut self) -> bool {
+        if !self.step(BUILD_STATES) {
+            return false;
+        }
+        let table = self.finish();
+        table.save_cached();
+        DistanceTable::install(table);
+        true
+    }
+}
+
+fn factorial(n: usize) -> usize {
+    (1..=n).product()
+}
+
+// Lehmer code of the permutation, read as a factorial-base number.
+fn rank(tiles: &[usize]) -> usize {
+    let mut rank = 0;
+    for i in 0..tiles.len() {
+        let smaller_after = tiles[i + 1..].iter().filter(|t| **t < tiles[i]).count();
+        rank = (rank * (tiles.len() - i)) + smaller_after;
+    }
+    rank
+}
+
+fn unrank(mut rank: usize, tiles: &mut [usize]) {
+    let cells = tiles.len();
+    let mut digits = vec![0; cells];
+    for i in (0..cells).rev() {
+        digits[i] = rank % (cells - i);
+        rank /= cells - i;
+    }
+    let mut remaining: Vec<usize> = (0..cells).collect();
+    for i in 0..cells {
+        tiles[i] = remaining.remove(digits[i]);
+    }
+}
+
+fn neighbours(cell: usize, rows: usize, cols: usize) -> [Option<usize>; 4] {
+    let (row, col) = (cell / cols, cell % cols);
+    [
+        if col > 0 { Some(cell - 1) } else { None },
+        if col + 1 < cols { Some(cell + 1) } else { None },
+        if row > 0 { Some(cell - cols) } else { None },
+        if row + 1 < rows {
+            Some(cell + cols)
+        } else {
+            None
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::npuzzle::GenerationMetric;
+
+    #[test]
+    fn bytes_round_trip() {
+        let table = DistanceTable::build(2, 3, 5);
+        let loaded = DistanceTable::from_bytes(&table.to_bytes()).unwrap();
+        assert_eq!(loaded.distances, table.distances);
+        assert_eq!(loaded.get_max_depth(), table.get_max_depth());
+        assert!(DistanceTable::from_bytes(&table.to_bytes()[1..]).is_err());
+    }
+
+    #[test]
+    fn distances_match_a_star() {
+        for seed in 0..30 {
+            let mut board = NBoard::new(3, 3);
+            board.generate_seeded(GenerationMetric::UniformRandom, seed);
+            let steps = board
+                .weighted_a_star_search(NBoard::manhattan_distance, 1., &mut Default::default())
+                .expect("every generated board is solvable");
+            assert_eq!(board.optimal_distance(), Some(steps.len()), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn boards_without_a_blank_have_no_distance() {
+        let table = DistanceTable::build(2, 2, 3);
+        assert_eq!(table.distance(&NBoard::new(2, 2)), None);
+        assert_eq!(NBoard::new(2, 2).optimal_distance(), None);
+    }
+
+    #[test]
+    fn builder_installs_the_same_table() {
+        let mut builder = TableBuilder::new(2, 3, 1);
+        let mut steps = 1;
+        while !builder.build() {
+            steps += 1;
+        }
+        assert!(steps > 1);
+        let installed = DistanceTable::cached(2, 3, 1).unwrap();
+        let table = DistanceTable::build(2, 3, 1);
+        assert_eq!(installed.distances, table.distances);
+        assert_eq!(installed.get_max_depth(), table.get_max_depth());
+        assert!(DistanceTable::builder(2, 3, 1).is_none());
+    }
+}
@@ -16,7 +16,11 @@ use js_sys::{Array, ArrayBuffer, Uint8Array};
 #[cfg(target_arch = "wasm32")]
 use poll_promise::Promise;
 #[cfg(target_arch = "wasm32")]
-use web_sys::{window, File, FileReader, HtmlElement, HtmlInputElement, Url};
+use wasm_bindgen::JsCast;
+#[cfg(target_arch = "wasm32")]
+use web_sys::{
+    window, Blob, File, FileReader, HtmlAnchorElement, HtmlElement, HtmlInputElement, Url,
+};
 
 pub struct FileDialog {
     file: Option<Vec<u8>>,
@@ -82,7 +86,6 @@ impl FileDialog {
         std::mem::replace(&mut self.file, None)
     }
 
-    #[allow(unused)]
     #[cfg(not(target_arch = "wasm32"))]
     pub fn save(&self, filename: &str, file: Vec<u8>) {
         let path = rfd::FileDialog::new().set_file_name(filename).save_file();
@@ -91,4 +94,26 @@ impl FileDialog {
             std::fs::write(path, file).ok();
         }
     }
+
+    // Browsers only save what a link downloads, so the bytes are handed to a throwaway one.
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self, filename: &str, file: Vec<u8>) {
+        let parts = Array::of1(&Uint8Array::from(file.as_slice()));
+        let Ok(blob) = Blob::new_with_u8_array_sequence(&parts) else {
+            return;
+        };
+        let Ok(url) = Url::create_object_url_with_blob(&blob) else {
+            return;
+        };
+        let link = window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.create_element("a").ok())
+            .and_then(|element| element.dyn_into::<HtmlAnchorElement>().ok());
+        if let Some(link) = link {
+            link.set_href(&url);
+            link.set_download(filename);
+            link.click();
+        }
+        Url::revoke_object_url(&url).ok();
+    }
 }
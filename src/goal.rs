@@ -0,0 +1,121 @@
+/**
+ * @file goal.rs
+ *
+ * @brief This is the module that describes which cell every tile of an NBoard belongs in once the
+ * puzzle is solved.
+ *
+ * Layouts follow the numbering of the 42-school n-puzzle, where tile t carries the number t + 1 and
+ * the last tile is the blank (0):
+ *
+ *   RowMajor     BlankFirst     Snail
+ *   1 2 3        0 1 2          1 2 3
+ *   4 5 6        3 4 5          8 0 4
+ *   7 8 0        6 7 8          7 6 5
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use crate::puzzle_file;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Goal {
+    #[default]
+    RowMajor,
+    BlankFirst,
+    Snail,
+    // goal cell of every tile, indexed by tile
+    Custom(Vec<usize>),
+}
+
+impl std::fmt::Display for Goal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Goal::RowMajor => write!(f, "Row Major"),
+            Goal::BlankFirst => write!(f, "Blank First"),
+            Goal::Snail => write!(f, "Snail"),
+            Goal::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Goal {
+    // A solved layout written in the puzzle file format, along with its size.
+    pub fn from_text(text: &str) -> Result<(usize, usize, Self), String> {
+        puzzle_file::read_goal(text)
+    }
+
+    // The tile a shuffle leaves out as the blank. Every layout but RowMajor puts the blank in a cell
+    // of its own, so it is the last tile, the 0 of a puzzle file. A RowMajor picture can be missing
+    // any one of its tiles.
+    pub fn blank_tile(&self, m: usize, n: usize) -> Option<usize> {
+        match self {
+            Goal::RowMajor => None,
+            _ => Some(m * n - 1),
+        }
+    }
+
+    // The goal cell of every tile on an m x n board, indexed by tile.
+    pub fn cells(&self, m: usize, n: usize) -> Result<Vec<usize>, String> {
+        let tile_count = m * n;
+        match self {
+            Goal::RowMajor => Ok((0..tile_count).collect()),
+            Goal::BlankFirst => Ok((0..tile_count).map(|t| (t + 1) % tile_count).collect()),
+            Goal::Snail => Ok(snail(m, n)),
+            Goal::Custom(cells) => {
+                if cells.len() != tile_count {
+                    return Err(format!(
+                        "a {}x{} goal needs {} cells, got {}",
+                        m,
+                        n,
+                        tile_count,
+                        cells.len()
+                    ));
+                }
+                let mut seen = vec![false; tile_count];
+                for (tile, cell) in cells.iter().enumerate() {
+                    if *cell >= tile_count {
+                        return Err(format!(
+                            "tile {} is sent to cell {}, which is off a {}x{} board",
+                            tile, cell, m, n
+                        ));
+                    }
+                    if seen[*cell] {
+                        return Err(format!("cell {} is the goal of more than one tile", cell));
+                    }
+                    seen[*cell] = true;
+                }
+                Ok(cells.clone())
+            }
+        }
+    }
+}
+
+// Cells visited clockwise from the top left corner, spiralling inwards.
+fn snail(m: usize, n: usize) -> Vec<usize> {
+    let mut cells = Vec::with_capacity(m * n);
+    let (mut top, mut bottom, mut left, mut right) = (0, m as isize - 1, 0, n as isize - 1);
+    while top <= bottom && left <= right {
+        for column in left..=right {
+            cells.push((top * n as isize + column) as usize);
+        }
+        for row in (top + 1)..=bottom {
+            cells.push((row * n as isize + right) as usize);
+        }
+        if top < bottom {
+            for column in (left..right).rev() {
+                cells.push((bottom * n as isize + column) as usize);
+            }
+        }
+        if left < right {
+            for row in ((top + 1)..bottom).rev() {
+                cells.push((row * n as isize + left) as usize);
+            }
+        }
+        top += 1;
+        bottom -= 1;
+        left += 1;
+        right -= 1;
+    }
+    cells
+}
@@ -0,0 +1,359 @@
+/**
+ * @file layered.rs
+ *
+ * @brief This is the module that solves boards of any size the way a person would: one row or
+ * column at a time, shrinking the board until only a 2x3 corner is left.
+ *
+ * Each outer line is placed a tile at a time, walking the tile along a shortest path while the blank
+ * goes around it, and locking it once home. The last two tiles of a line are brought close and
+ * then sorted out together by a small breadth-first search, since locking the second to last too
+ * early can leave the last one with no way in. The peeled line is always the one the blank doesn't
+ * belong in, so any goal works. The corner's 360 reachable states are few enough that it is
+ * finished with a breadth-first search.
+ *
+ * Solutions are valid but far from optimal, in exchange for taking milliseconds on 10x10 and larger
+ * boards that A* and IDA* will never finish.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use crate::npuzzle::NBoard;
+use crate::solver_stats::SolverStats;
+use std::collections::{HashMap, VecDeque};
+
+const UNREACHED: usize = usize::MAX;
+
+pub fn solve(board: &NBoard, stats: &mut SolverStats) -> Option<Vec<usize>> {
+    if !board.solvable() {
+        return None;
+    }
+    let mut layered = Layered::new(board);
+    layered.solve(stats)?;
+    stats.record_frontier(
+        layered.steps.len(),
+        (layered.tiles.len() * (2 * std::mem::size_of::<usize>() + 1))
+            + (layered.steps.len() * std::mem::size_of::<usize>()),
+    );
+    Some(layered.steps)
+}
+
+struct Layered {
+    m: usize,
+    n: usize,
+    // the goal cell of the tile in each cell, so a tile is home when its label equals its cell
+    tiles: Vec<usize>,
+    blank: usize,
+    blank_goal: usize,
+    locked: Vec<bool>,
+    steps: Vec<usize>,
+}
+
+impl Layered {
+    fn new(board: &NBoard) -> Self {
+        let cells = board.get_m() * board.get_n();
+        let blank = board.get_missing_index();
+        Self {
+            m: board.get_m(),
+            n: board.get_n(),
+            tiles: (0..cells)
+                .map(|cell| board.goal_cell(board.index_at(cell)))
+                .collect(),
+            blank,
+            blank_goal: board.goal_cell(board.index_at(blank)),
+            locked: vec![false; cells],
+            steps: Vec::default(),
+        }
+    }
+
+    fn solve(&mut self, stats: &mut SolverStats) -> Option<()> {
+        let (mut top, mut bottom, mut left, mut right) = (0, self.m - 1, 0, self.n - 1);
+        let (blank_row, blank_col) = (self.blank_goal / self.n, self.blank_goal % self.n);
+        let n = self.n as isize;
+        while (bottom - top + 1) * (right - left + 1) > 6 {
+            if bottom - top > 1 && bottom - top >= right - left {
+                if blank_row > top {
+                    let line = (left..=right).map(|col| top * self.n + col).collect();
+                    self.peel(line, n, stats)?;
+                    top += 1;
+                } else {
+                    let line = (left..=right).map(|col| bottom * self.n + col).collect();
+                    self.peel(line, -n, stats)?;
+                    bottom -= 1;
+                }
+            } else if blank_col > left {
+                let line = (top..=bottom).map(|row| row * self.n + left).collect();
+                self.peel(line, 1, stats)?;
+                left += 1;
+            } else {
+                let line = (top..=bottom).map(|row| row * self.n + right).collect();
+                self.peel(line, -1, stats)?;
+                right -= 1;
+            }
+        }
+        self.finish(stats)
+    }
+
+    // Places and locks every cell of `line`. `inward` is the offset from a cell of the line to its
+    // neighbour on the side of the board that is still unsolved.
+    fn peel(&mut self, line: Vec<usize>, inward: isize, stats: &mut SolverStats) -> Option<()> {
+        for cell in &line[..line.len() - 2] {
+            self.place(*cell, *cell, stats)?;
+            self.locked[*cell] = true;
+        }
+
+        let (a, b) = (line[line.len() - 2], line[line.len() - 1]);
+        if self.tiles[a] != a || self.tiles[b] != b {
+            // b's tile waits in a's cell while a's tile and the blank are brought within two cells
+            // of the line, then the pair is sorted out inside that 2x3 window
+            let inside = |cell: usize| (cell as isize + inward) as usize;
+            let window = [
+                a,
+                b,
+                inside(a),
+                inside(b),
+                inside(inside(a)),
+                inside(inside(b)),
+            ];
+            self.place(b, a, stats)?;
+            self.locked[a] = true;
+            if !window.contains(&self.position(a)?) {
+                self.place(a, inside(a), stats)?;
+            }
+            let tile = self.position(a)?;
+            if !window.contains(&self.blank) {
+                self.locked[tile] = true;
+                let path = window
+                    .iter()
+                    .find_map(|cell| self.path(self.blank, *cell, None, stats))?;
+                for cell in path {
+                    self.step(cell);
+                }
+                self.locked[tile] = false;
+            }
+            self.locked[a] = false;
+            self.arrange(&[a, b], Some(&window), stats)?;
+        }
+        self.locked[a] = true;
+        self.locked[b] = true;
+        Some(())
+    }
+
+    // Brings the tile labelled `label` to `target` without disturbing any locked cell.
+    fn place(&mut self, label: usize, target: usize, stats: &mut SolverStats) -> Option<()> {
+        let mut tile = self.position(label)?;
+        if tile == target {
+            return Some(());
+        }
+
+        let path = self.path(tile, target, None, stats)?;
+        for next in path {
+            // walk the blank round to the tile's next cell, then swap the two
+            if self.move_blank_avoiding(next, Some(tile), stats).is_none() {
+                return self.arrange_to(&[label], &[target], None, stats);
+            }
+            self.step(tile);
+            tile = next;
+        }
+        Some(())
+    }
+
+    // Breadth-first over every placement of the tiles labelled `labels` and the blank, for the rare
+    // spot where the blank can't get round a tile the simple way. The blank stays inside `area` when
+    // one is given. Each tile ends on its own cell.
+    fn arrange(
+        &mut self,
+        labels: &[usize],
+        area: Option<&[usize]>,
+        stats: &mut SolverStats,
+    ) -> Option<()> {
+        self.arrange_to(labels, labels, area, stats)
+    }
+
+    fn arrange_to(
+        &mut self,
+        labels: &[usize],
+        targets: &[usize],
+        area: Option<&[usize]>,
+        stats: &mut SolverStats,
+    ) -> Option<()> {
+        let tiles = labels
+            .iter()
+            .map(|label| self.position(*label))
+            .collect::<Option<Vec<usize>>>()?;
+        let start = (tiles, self.blank);
+        let mut came_from: HashMap<(Vec<usize>, usize), (Vec<usize>, usize)> = HashMap::new();
+        // each state is queued with where the blank was before it, as NBoard::successors does
+        let mut queue = VecDeque::from([(start.clone(), None)]);
+        came_from.insert(start.clone(), start.clone());
+        let mut goal = None;
+        while let Some(((tiles, blank), previous_blank)) = queue.pop_front() {
+            if tiles == targets {
+                goal = Some((tiles, blank));
+                break;
+            }
+            stats.expanded += 1;
+            for next_blank in self.free_neighbours(blank) {
+                if Some(next_blank) == previous_blank
+                    || matches!(area, Some(area) if !area.contains(&next_blank))
+                {
+                    continue;
+                }
+                let next_tiles = tiles
+                    .iter()
+                    .map(|tile| if *tile == next_blank { blank } else { *tile })
+                    .collect();
+                let next = (next_tiles, next_blank);
+                stats.generated += 1;
+                if !came_from.contains_key(&next) {
+                    came_from.insert(next.clone(), (tiles.clone(), blank));
+                    queue.push_back((next, Some(blank)));
+                }
+            }
+        }
+
+        let mut state = goal?;
+        let mut blanks = Vec::default();
+        while state != start {
+            blanks.push(state.1);
+            state = came_from[&state].clone();
+        }
+        for blank in blanks.into_iter().rev() {
+            self.step(blank);
+        }
+        Some(())
+    }
+
+    fn move_blank_avoiding(
+        &mut self,
+        target: usize,
+        avoid: Option<usize>,
+        stats: &mut SolverStats,
+    ) -> Option<()> {
+        for cell in self.path(self.blank, target, avoid, stats)? {
+            self.step(cell);
+        }
+        Some(())
+    }
+
+    // The cells after `from` on a shortest path to `to` through unlocked cells other than `avoid`.
+    fn path(
+        &self,
+        from: usize,
+        to: usize,
+        avoid: Option<usize>,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        let mut came_from = vec![UNREACHED; self.tiles.len()];
+        came_from[from] = from;
+        let mut queue = VecDeque::from([from]);
+        while let Some(cell) = queue.pop_front() {
+            if cell == to {
+                break;
+            }
+            stats.expanded += 1;
+            for next in self.free_neighbours(cell) {
+                stats.generated += 1;
+                if came_from[next] == UNREACHED && Some(next) != avoid {
+                    came_from[next] = cell;
+                    queue.push_back(next);
+                }
+            }
+        }
+        if came_from[to] == UNREACHED {
+            return None;
+        }
+
+        let mut path = Vec::default();
+        let mut cell = to;
+        while cell != from {
+            path.push(cell);
+            cell = came_from[cell];
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    // The last unlocked corner, 2x3 or 3x2 (2x2 on a 2x2 board), where every tile but the blank
+    // goes home together.
+    fn finish(&mut self, stats: &mut SolverStats) -> Option<()> {
+        let labels: Vec<usize> = (0..self.tiles.len())
+            .filter(|cell| !self.locked[*cell] && *cell != self.blank_goal)
+            .collect();
+        self.arrange(&labels, None, stats)
+    }
+
+    fn position(&self, label: usize) -> Option<usize> {
+        self.tiles.iter().position(|t| *t == label)
+    }
+
+    fn free_neighbours(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        let (row, col) = (cell / self.n, cell % self.n);
+        [
+            (row > 0).then(|| cell - self.n),
+            (row + 1 < self.m).then(|| cell + self.n),
+            (col > 0).then(|| cell - 1),
+            (col + 1 < self.n).then(|| cell + 1),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|next| !self.locked[*next])
+    }
+
+    // Moves the blank into the neighbouring `cell`.
+    fn step(&mut self, cell: usize) {
+        self.tiles.swap(self.blank, cell);
+        self.blank = cell;
+        self.steps.push(cell);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::goal::Goal;
+    use crate::npuzzle::{GenerationMetric, NBoard};
+
+    fn assert_solves(board: &NBoard) {
+        let steps = board
+            .layered_solve()
+            .expect("every generated board is solvable");
+        let verified = board
+            .moves_from_indices(&steps)
+            .and_then(|moves| board.verify_moves(&moves));
+        assert_eq!(verified, Ok(()), "\n{}", board);
+    }
+
+    #[test]
+    fn solves_small_boards() {
+        for (m, n) in [(2, 2), (2, 3), (3, 2), (3, 3), (2, 5), (5, 2), (4, 4)] {
+            for seed in 0..10 {
+                let mut board = NBoard::new(m, n);
+                board.generate_seeded(GenerationMetric::UniformRandom, seed);
+                assert_solves(&board);
+            }
+        }
+    }
+
+    #[test]
+    fn solves_large_boards() {
+        for size in [8, 10, 12] {
+            for seed in 0..3 {
+                let mut board = NBoard::new(size, size);
+                board.generate_seeded(GenerationMetric::UniformRandom, seed);
+                assert_solves(&board);
+            }
+        }
+    }
+
+    #[test]
+    fn solves_towards_any_goal() {
+        for goal in [Goal::BlankFirst, Goal::Snail] {
+            for (m, n) in [(3, 3), (4, 5), (10, 10)] {
+                let mut board = NBoard::new(m, n);
+                board.set_goal(&goal).unwrap();
+                board.generate_seeded(GenerationMetric::UniformRandom, 7);
+                assert_solves(&board);
+            }
+        }
+    }
+}
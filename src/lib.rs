@@ -11,11 +11,25 @@
 mod app;
 pub use app::NPuzzle;
 mod about_panel;
+mod distance_table;
 mod fd;
 mod gallery_panel;
+mod goal;
 mod image_helpers;
+mod layered;
+mod move_pruning;
+mod moves;
 mod npuzzle;
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+mod parallel_a_star;
+mod pattern_database;
+mod puzzle_file;
 mod puzzle_panel;
+mod search;
 mod settings_panel;
+mod solution;
+mod solver;
+mod solver_stats;
+mod walking_distance;
 mod web_helpers;
 const MAX_WRAP: f32 = 1000.0;
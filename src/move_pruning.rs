@@ -0,0 +1,233 @@
+/**
+ * @file move_pruning.rs
+ *
+ * @brief This is the module that keeps depth-first searches from walking the same stretch of board
+ * twice, by pruning move sequences that another, no longer sequence already covers.
+ *
+ * A MovePruner is a finite-state machine over the moves made so far. It is built by enumerating every
+ * string of blank moves up to `max_length` on an unbounded board and recording what each one does to
+ * the tiles. A string is a duplicate when an earlier string, shorter or the same length and
+ * alphabetically first, has the same effect without straying outside the cells the duplicate visits,
+ * so it is playable wherever the duplicate is. The duplicates are then matched Aho-Corasick style, so
+ * a search only ever carries one small state per node.
+ *
+ * The shortest duplicates are the moves straight back, so a pruner of length 2 only cuts those.
+ * Longer ones also cut going round a 2x2 block the long way, and so on. Pruning never loses the
+ * first shortest solution in alphabetical order, so optimal searches stay optimal.
+ *
+ * Graph searches such as A* already skip states they have seen, and keep whichever path reached
+ * them first, which a pruner could cut. They only skip the move straight back.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use crate::moves::Move;
+use log::debug;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+// Moves straight back, and nothing else.
+pub const INVERSE_LENGTH: usize = 2;
+// Long enough to cut most duplicate paths, while still building in about half a second.
+pub const DUPLICATE_PATH_LENGTH: usize = 10;
+
+const PRUNED: u32 = u32::MAX;
+
+static PRUNERS: Mutex<Option<HashMap<usize, Arc<MovePruner>>>> = Mutex::new(None);
+
+type Cell = (i8, i8);
+// where a string leaves the blank, and where each tile it displaced started
+type Effect = (Cell, Vec<(Cell, Cell)>);
+
+pub struct MovePruner {
+    // the next state for each state and move, or PRUNED
+    transitions: Vec<[u32; 4]>,
+}
+
+// A string of moves, by where it leaves the blank and the tiles it displaces, and the box of cells
+// it visits.
+struct Walk {
+    moves: Vec<Move>,
+    blank: Cell,
+    tiles: Vec<(Cell, Cell)>,
+    bounds: [i8; 4],
+}
+
+impl MovePruner {
+    pub const START: usize = 0;
+
+    // Pruners are the same for every board, so they are built once per process and handed out
+    // behind an Arc.
+    pub fn get(max_length: usize) -> Arc<Self> {
+        let mut pruners = PRUNERS.lock().unwrap();
+        pruners
+            .get_or_insert_with(HashMap::new)
+            .entry(max_length)
+            .or_insert_with(|| Arc::new(Self::build(max_length)))
+            .clone()
+    }
+
+    // The state after `mv`, or None if the moves so far end in a duplicate.
+    pub fn next(&self, state: usize, mv: Move) -> Option<usize> {
+        match self.transitions[state][mv as usize] {
+            PRUNED => None,
+            next => Some(next as usize),
+        }
+    }
+
+    fn build(max_length: usize) -> Self {
+        let duplicates = Self::duplicates(max_length);
+        let pruner = Self::matcher(&duplicates);
+        debug!(
+            "move pruner: {} duplicate strings of at most {} moves, {} states",
+            duplicates.len(),
+            max_length,
+            pruner.transitions.len()
+        );
+        pruner
+    }
+
+    // Every string of at most `max_length` moves that is a duplicate while none of the strings inside
+    // it are, in the order they were found.
+    fn duplicates(max_length: usize) -> Vec<Vec<Move>> {
+        let mut duplicates: Vec<Vec<Move>> = Vec::default();
+        let mut known: HashSet<Vec<Move>> = HashSet::default();
+        // the boxes of the strings kept so far, by their effect
+        let mut effects: HashMap<Effect, Vec<[i8; 4]>> = HashMap::default();
+        let empty = Walk {
+            moves: Vec::default(),
+            blank: (0, 0),
+            tiles: Vec::default(),
+            bounds: [0; 4],
+        };
+        effects.insert((empty.blank, Vec::default()), vec![empty.bounds]);
+
+        let mut layer = vec![empty];
+        for _ in 0..max_length {
+            let mut next_layer = Vec::default();
+            for walk in &layer {
+                for mv in Move::ALL {
+                    let next = walk.then(mv);
+                    let contains_duplicate =
+                        (1..next.moves.len()).any(|i| known.contains(&next.moves[i..]));
+                    if contains_duplicate {
+                        continue;
+                    }
+                    let boxes = effects.entry((next.blank, next.tiles.clone())).or_default();
+                    if boxes.iter().any(|bounds| within(bounds, &next.bounds)) {
+                        known.insert(next.moves.clone());
+                        duplicates.push(next.moves);
+                    } else {
+                        boxes.push(next.bounds);
+                        next_layer.push(next);
+                    }
+                }
+            }
+            layer = next_layer;
+        }
+        duplicates
+    }
+
+    // An Aho-Corasick automaton over the duplicates: states are the prefixes of duplicates, and a
+    // move follows the longest prefix that the moves so far still end in.
+    fn matcher(duplicates: &[Vec<Move>]) -> Self {
+        let mut transitions: Vec<[u32; 4]> = vec![[0; 4]];
+        let mut children: Vec<[Option<u32>; 4]> = vec![[None; 4]];
+        let mut terminal = vec![false];
+        for duplicate in duplicates {
+            let mut state = 0;
+            for mv in duplicate {
+                state = match children[state][*mv as usize] {
+                    Some(child) => child as usize,
+                    None => {
+                        children.push([None; 4]);
+                        terminal.push(false);
+                        children[state][*mv as usize] = Some(children.len() as u32 - 1);
+                        children.len() - 1
+                    }
+                };
+            }
+            terminal[state] = true;
+        }
+        transitions.resize(children.len(), [0; 4]);
+
+        // breadth-first, so a state's fallback is always finished before the state itself
+        let mut fallback = vec![0; children.len()];
+        let mut queue = VecDeque::from([0]);
+        while let Some(state) = queue.pop_front() {
+            terminal[state] = terminal[state] || terminal[fallback[state]];
+            for mv in Move::ALL {
+                let i = mv as usize;
+                match children[state][i] {
+                    Some(child) => {
+                        let child = child as usize;
+                        fallback[child] = match state {
+                            0 => 0,
+                            _ => transitions[fallback[state]][i] as usize,
+                        };
+                        transitions[state][i] = child as u32;
+                        queue.push_back(child);
+                    }
+                    None => {
+                        transitions[state][i] = match state {
+                            0 => 0,
+                            _ => transitions[fallback[state]][i],
+                        };
+                    }
+                }
+            }
+        }
+
+        for state in transitions.iter_mut() {
+            for next in state.iter_mut() {
+                if terminal[*next as usize] {
+                    *next = PRUNED;
+                }
+            }
+        }
+        Self { transitions }
+    }
+}
+
+impl Walk {
+    fn then(&self, mv: Move) -> Self {
+        let (row, col) = self.blank;
+        let to = match mv {
+            Move::Up => (row - 1, col),
+            Move::Down => (row + 1, col),
+            Move::Left => (row, col - 1),
+            Move::Right => (row, col + 1),
+        };
+
+        // the tile at `to`, named by the cell it started in, slides into the blank's cell
+        let mut tiles = self.tiles.clone();
+        let moved = match tiles.iter().position(|(cell, _)| *cell == to) {
+            Some(i) => tiles.remove(i).1,
+            None => to,
+        };
+        if moved != self.blank {
+            tiles.push((self.blank, moved));
+        }
+        tiles.sort_unstable();
+
+        let mut moves = self.moves.clone();
+        moves.push(mv);
+        let [top, bottom, left, right] = self.bounds;
+        Self {
+            moves,
+            blank: to,
+            tiles,
+            bounds: [
+                top.min(to.0),
+                bottom.max(to.0),
+                left.min(to.1),
+                right.max(to.1),
+            ],
+        }
+    }
+}
+
+fn within(inner: &[i8; 4], outer: &[i8; 4]) -> bool {
+    inner[0] >= outer[0] && inner[1] <= outer[1] && inner[2] >= outer[2] && inner[3] <= outer[3]
+}
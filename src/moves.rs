@@ -0,0 +1,182 @@
+/**
+ * @file moves.rs
+ *
+ * @brief This is the module that gives solutions a compact, human readable form: a string of U, D,
+ * L and R, one letter per move.
+ *
+ * A Move always records which way the blank went. Strings can be read and written in either
+ * convention: "blank moves", where the letter is the blank's direction, or "tile moves", where it
+ * is the direction of the tile that slides into the blank, which is the opposite one.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Convention {
+    #[default]
+    BlankMoves,
+    TileMoves,
+}
+
+impl std::fmt::Display for Convention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Convention::BlankMoves => write!(f, "Blank Moves"),
+            Convention::TileMoves => write!(f, "Tile Moves"),
+        }
+    }
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl Move {
+    pub const ALL: [Move; 4] = [Move::Up, Move::Down, Move::Left, Move::Right];
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Move::Up => Move::Down,
+            Move::Down => Move::Up,
+            Move::Left => Move::Right,
+            Move::Right => Move::Left,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Move::Up => 'U',
+            Move::Down => 'D',
+            Move::Left => 'L',
+            Move::Right => 'R',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'U' => Some(Move::Up),
+            'D' => Some(Move::Down),
+            'L' => Some(Move::Left),
+            'R' => Some(Move::Right),
+            _ => None,
+        }
+    }
+
+    // Where the blank ends up when it leaves `blank` this way on an m x n board, if it stays on it.
+    pub fn target(self, blank: usize, m: usize, n: usize) -> Option<usize> {
+        let (row, col) = (blank / n, blank % n);
+        match self {
+            Move::Up if row > 0 => Some(blank - n),
+            Move::Down if row + 1 < m => Some(blank + n),
+            Move::Left if col > 0 => Some(blank - 1),
+            Move::Right if col + 1 < n => Some(blank + 1),
+            _ => None,
+        }
+    }
+
+    // The move taking the blank from `blank` to the neighbouring cell `to`.
+    pub fn between(blank: usize, to: usize, m: usize, n: usize) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mv| mv.target(blank, m, n) == Some(to))
+    }
+}
+
+// Whitespace is ignored, so long solutions can be wrapped or grouped.
+pub fn parse_moves(text: &str, convention: Convention) -> Result<Vec<Move>, String> {
+    let mut moves = Vec::default();
+    for (position, c) in text.chars().enumerate() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let Some(mv) = Move::from_char(c) else {
+            return Err(format!(
+                "unexpected '{}' at position {}, expected one of U, D, L or R",
+                c,
+                position + 1
+            ));
+        };
+        moves.push(match convention {
+            Convention::BlankMoves => mv,
+            Convention::TileMoves => mv.opposite(),
+        });
+    }
+    Ok(moves)
+}
+
+pub fn format_moves(moves: &[Move], convention: Convention) -> String {
+    moves
+        .iter()
+        .map(|mv| match convention {
+            Convention::BlankMoves => mv.to_char(),
+            Convention::TileMoves => mv.opposite().to_char(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::npuzzle::{GenerationMetric, NBoard};
+
+    #[test]
+    fn parse_and_format_round_trip() {
+        for convention in [Convention::BlankMoves, Convention::TileMoves] {
+            let moves = parse_moves("RDLL URD\nu", convention).unwrap();
+            assert_eq!(moves.len(), 8);
+            assert_eq!(format_moves(&moves, convention), "RDLLURDU");
+        }
+    }
+
+    #[test]
+    fn tile_moves_are_the_opposite_of_blank_moves() {
+        let blank = parse_moves("UDLR", Convention::BlankMoves).unwrap();
+        let tile = parse_moves("UDLR", Convention::TileMoves).unwrap();
+        assert_eq!(blank, [Move::Up, Move::Down, Move::Left, Move::Right]);
+        assert_eq!(tile, [Move::Down, Move::Up, Move::Right, Move::Left]);
+        assert_eq!(format_moves(&blank, Convention::TileMoves), "DURL");
+        for mv in Move::ALL {
+            assert_eq!(mv.opposite().opposite(), mv);
+        }
+    }
+
+    #[test]
+    fn invalid_characters_are_reported_where_they_are() {
+        assert_eq!(
+            parse_moves("UD x", Convention::BlankMoves),
+            Err("unexpected 'x' at position 4, expected one of U, D, L or R".to_owned())
+        );
+    }
+
+    #[test]
+    fn illegal_moves_leave_the_board_untouched() {
+        let mut board = NBoard::new(3, 3);
+        board.generate_seeded(GenerationMetric::UniformRandom, 4);
+        let before = board.clone();
+        // up to the top row is legal, one more is not
+        let row = board.get_missing_index() / 3;
+        let moves = vec![Move::Up; row + 1];
+        let error = board.apply_moves(&moves).unwrap_err();
+        assert!(
+            error.starts_with(&format!("move {} (U)", row + 1)),
+            "{}",
+            error
+        );
+        assert_eq!(board, before);
+
+        board.apply_moves(&moves[..row]).unwrap();
+        assert_eq!(board.get_missing_index(), before.get_missing_index() % 3);
+    }
+}
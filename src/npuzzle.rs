@@ -1,8 +1,8 @@
 /**
  * @file npuzzle.rs
  *
- * @brief This is the npuzzle module which implements the logic for an NxN Board with it's accompanying,
- * uninformed and heuristic-based search algorithms. (DFS and A*)
+ * @brief This is the npuzzle module which implements the logic for an MxN Board with it's accompanying,
+ * uninformed and heuristic-based search algorithms. (DFS, IDDFS, A*, bidirectional A* and IDA*)
  *
  * Generation technique and is_solvable is adapted from:
  * https://github.com/tnicolas42/n-puzzle/blob/master/generator.py
@@ -17,19 +17,63 @@
  * Contact: stephenfoster@nevada.unr.edu
  *
  */
-use rand::Rng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 const N_MIN: usize = 2;
 use log::{debug, error, info};
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 use std::collections::BinaryHeap;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::distance_table::DistanceTable;
+use crate::goal::Goal;
+use crate::layered;
+use crate::move_pruning::{MovePruner, INVERSE_LENGTH};
+use crate::moves::Move;
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+use crate::parallel_a_star;
+use crate::puzzle_file;
+use crate::search::{run_to_completion, Expand, SearchStatus};
+use crate::solution;
+use crate::solver_stats::SolverStats;
+use crate::walking_distance::WalkingDistanceTable;
+
+// Hill-climbing generation takes this many steps per tile, and now and then accepts a step that
+// lowers its metric so it can wander off a local maximum.
+const CLIMB_STEPS_PER_TILE: usize = 100;
+const CLIMB_ESCAPE_PROBABILITY: f64 = 0.1;
+// Boards too large for a distance table get this many random walks checked by IDA* before
+// generate_at_depth gives up.
+const DEPTH_ATTEMPTS: usize = 200;
+// Weighted costs are kept as fixed point so the heap can stay on integers.
+const COST_SCALE: f64 = 1000.;
+// ARA* lowers its weight by this much after every solution, and checks the clock this often.
+const ARA_WEIGHT_STEP: f64 = 0.5;
+const ARA_CLOCK_INTERVAL: usize = 1024;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum GenerationMetric {
+pub enum GenerationMetric {
     MaxManhattanDistance,
     MaxEuclideanDistance,
     MaxTilesOut,
+    OptimalDepth(usize, usize),
     Random(usize),
+    UniformRandom,
+}
+
+impl std::fmt::Display for GenerationMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerationMetric::MaxManhattanDistance => write!(f, "Max Manhattan Distance"),
+            GenerationMetric::MaxEuclideanDistance => write!(f, "Max Euclidean Distance"),
+            GenerationMetric::MaxTilesOut => write!(f, "Max Tiles Out"),
+            GenerationMetric::OptimalDepth(min, max) => write!(f, "{}-{} Moves", min, max),
+            GenerationMetric::Random(move_count) => write!(f, "Random ({} moves)", move_count),
+            GenerationMetric::UniformRandom => write!(f, "Uniform Random"),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -70,6 +114,8 @@ struct State {
     cost: usize,
     board: NBoard,
     steps: Vec<usize>,
+    // where the blank was before the move that led here; None at the root
+    came_from: Option<usize>,
 }
 
 impl Ord for State {
@@ -90,32 +136,68 @@ impl PartialEq for State {
     }
 }
 
+// Up to 16 tiles fit 4 bits apiece in a u64; larger boards fall back to a byte per tile (two past
+// 256 tiles). The missing tile keeps its index, so the key still tells boards apart.
+const PACKED_TILES: usize = 16;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum StateKey {
+    Packed(u64),
+    Bytes(Box<[u8]>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnytimeSolution {
+    pub steps: Vec<usize>,
+    // proven upper bound on how many times longer than optimal `steps` is
+    pub bound: f64,
+}
+
+struct AnytimeNode {
+    board: NBoard,
+    g: usize,
+    h: usize,
+    parent: Option<(usize, usize)>,
+    closed: bool,
+    inconsistent: bool,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct NBoard {
+    m: usize,
     n: usize,
     board: Vec<Tile>,
     initial_board: Vec<Tile>,
     missing_index: usize,
+    // goal cell of every tile, shared between the many clones a search makes
+    goal: Arc<[usize]>,
+    // the tile shuffles leave out, or None for any
+    blank_tile: Option<usize>,
 }
 
 impl NBoard {
-    pub fn new(n: usize) -> Self {
+    // m rows of n columns
+    pub fn new(m: usize, n: usize) -> Self {
+        let goal: Arc<[usize]> = (0..m * n).collect::<Vec<usize>>().into();
         let mut initial_board = Vec::default();
-        Self::initialize_board(&mut initial_board, n);
+        Self::initialize_board(&mut initial_board, &goal);
         let board = initial_board.clone();
 
         Self {
+            m: m,
             n: n,
             board: board,
             initial_board,
             missing_index: 9999,
+            goal,
+            blank_tile: None,
         }
     }
 }
 
 impl Default for NBoard {
     fn default() -> Self {
-        Self::new(N_MIN)
+        Self::new(N_MIN, N_MIN)
     }
 }
 
@@ -124,7 +206,7 @@ use std::fmt;
 impl std::fmt::Display for NBoard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut s = "".to_owned();
-        for i in 0..self.n {
+        for i in 0..self.m {
             for j in 0..self.n {
                 s += &format!("{}", self.board.get((i * self.n) + j).unwrap())[..];
                 s += " ";
@@ -141,18 +223,26 @@ enum Punchout {
 }
 
 impl NBoard {
-    pub fn generate(&mut self) {
-        while {
+    // The same seed, metric and board size always give the same scramble, on native and web alike.
+    pub fn generate_seeded(&mut self, metric: GenerationMetric, seed: u64) {
+        self.generate_with_rng(metric, &mut Self::seeded_rng(seed));
+    }
+
+    // StdRng's algorithm may change between rand releases, ChaCha8Rng's output never will.
+    pub fn seeded_rng(seed: u64) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(seed)
+    }
+
+    // Every scramble is made of legal blank moves away from the goal, so it is always solvable.
+    // A scramble that happens to land back on the goal is thrown away and retried.
+    pub fn generate_with_rng<R: Rng>(&mut self, metric: GenerationMetric, rng: &mut R) {
+        loop {
             Self::reset(&mut self.board, &mut self.initial_board);
-            self.missing_index = Self::punchout(&mut self.board, Punchout::Random);
-            self.missing_index = Self::generate_puzzle(
-                &mut self.board,
-                self.missing_index,
-                GenerationMetric::Random(100),
-            );
-            !self.check_win()
-        } {
-            break;
+            self.punch_blank(rng);
+            self.generate_puzzle(metric, rng);
+            if !self.check_win() {
+                break;
+            }
         }
     }
 
@@ -164,6 +254,26 @@ impl NBoard {
         self.missing_index = mi;
     }
 
+    // Puts the board back in the solved state of the new goal.
+    pub fn set_goal(&mut self, goal: &Goal) -> Result<(), String> {
+        self.goal = goal.cells(self.m, self.n)?.into();
+        self.blank_tile = goal.blank_tile(self.m, self.n);
+        Self::initialize_board(&mut self.initial_board, &self.goal);
+        self.board = self.initial_board.clone();
+        self.missing_index = 9999;
+        Ok(())
+    }
+
+    pub fn goal_cell(&self, tile: usize) -> usize {
+        self.goal[tile]
+    }
+
+    // Where the blank belongs, or None before a tile has been left out.
+    pub fn blank_goal(&self) -> Option<usize> {
+        let tile = self.board.get(self.missing_index)?;
+        Some(self.goal[tile.index])
+    }
+
     pub fn reset(dst: &mut Vec<Tile>, src: &Vec<Tile>) {
         *dst = src.clone();
     }
@@ -180,10 +290,19 @@ impl NBoard {
         }
     }
 
-    fn punchout(board: &mut Vec<Tile>, punch: Punchout) -> usize {
+    // The goal's own blank if it has one, otherwise any tile.
+    fn punch_blank<R: Rng>(&mut self, rng: &mut R) {
+        let punch = match self.blank_tile {
+            Some(tile) => Punchout::Index(self.goal[tile]),
+            None => Punchout::Random,
+        };
+        self.missing_index = Self::punchout(&mut self.board, punch, rng);
+    }
+
+    fn punchout<R: Rng>(board: &mut Vec<Tile>, punch: Punchout, rng: &mut R) -> usize {
         match punch {
             Punchout::Random => {
-                let i: usize = rand::thread_rng().gen_range(0..board.len());
+                let i: usize = rng.gen_range(0..board.len());
                 board.get_mut(i).unwrap().tile_type = TileType::Missing;
                 i
             }
@@ -197,7 +316,7 @@ impl NBoard {
     pub fn check_win(&self) -> bool {
         for i in 0..self.board.len() {
             if let Some(tile) = self.board.get(i) {
-                if tile.index != i {
+                if self.goal[tile.index] != i {
                     break;
                 } else if i == (self.board.len() - 1) {
                     return true;
@@ -217,33 +336,252 @@ impl NBoard {
         with
     }
 
-    fn generate_puzzle(board: &mut Vec<Tile>, missing: usize, metric: GenerationMetric) -> usize {
+    fn generate_puzzle<R: Rng>(&mut self, metric: GenerationMetric, rng: &mut R) {
         match metric {
-            GenerationMetric::MaxTilesOut => 0,
-            GenerationMetric::MaxManhattanDistance => 0,
-            GenerationMetric::MaxEuclideanDistance => 0,
+            GenerationMetric::MaxTilesOut => self.climb(rng, |b| b.tiles_out() as f64),
+            GenerationMetric::MaxManhattanDistance => {
+                self.climb(rng, |b| b.manhattan_distance() as f64)
+            }
+            GenerationMetric::MaxEuclideanDistance => self.climb(rng, Self::euclidean_distance),
+            GenerationMetric::OptimalDepth(min, max) => {
+                if let Err(e) = self.scramble_to_depth(min, max, rng) {
+                    error!("{}", e);
+                    self.climb(rng, |b| b.manhattan_distance() as f64);
+                }
+            }
             GenerationMetric::Random(move_count) => {
-                let mut missing_index = missing;
                 for _ in 0..move_count {
-                    let available_to_swap =
-                        Self::get_available_to_swap(missing_index, Self::get_n_from_board(board));
+                    let available_to_swap = self.get_swappable();
 
                     let with = available_to_swap
-                        .get(rand::thread_rng().gen_range(0..available_to_swap.len()))
+                        .get(rng.gen_range(0..available_to_swap.len()))
                         .unwrap()
                         .clone();
 
-                    missing_index = Self::swap_with(missing_index, with, board);
+                    self.swap(with);
+                }
+            }
+            GenerationMetric::UniformRandom => self.shuffle_uniformly(rng),
+        }
+    }
+
+    // Every solvable arrangement is equally likely: the tiles, blank included, are shuffled freely,
+    // and a shuffle that lands in the unsolvable half has two in-play tiles swapped. That flips the
+    // inversion parity without moving the blank, and pairs the two halves up one to one.
+    fn shuffle_uniformly<R: Rng>(&mut self, rng: &mut R) {
+        self.board.shuffle(rng);
+        self.missing_index = self
+            .board
+            .iter()
+            .position(|tile| tile.tile_type == TileType::Missing)
+            .unwrap();
+
+        if !Self::is_solvable(&self.board, self.missing_index, self.n, &self.goal) {
+            let in_play: Vec<usize> = (0..3).filter(|i| *i != self.missing_index).collect();
+            self.board.swap(in_play[0], in_play[1]);
+        }
+    }
+
+    // Scrambles to a board whose optimal solution is between `min` and `max` moves, returning that
+    // length.
+    pub fn generate_at_depth<R: Rng>(
+        &mut self,
+        min: usize,
+        max: usize,
+        rng: &mut R,
+    ) -> Result<usize, String> {
+        Self::reset(&mut self.board, &mut self.initial_board);
+        self.punch_blank(rng);
+        self.scramble_to_depth(min, max, rng)
+    }
+
+    // Small boards pick uniformly among every state at those depths. Larger ones take random walks
+    // away from the goal and have IDA* measure how deep each really landed, lengthening or
+    // shortening the next walk until one falls in range.
+    fn scramble_to_depth<R: Rng>(
+        &mut self,
+        min: usize,
+        max: usize,
+        rng: &mut R,
+    ) -> Result<usize, String> {
+        if min > max {
+            return Err(format!("no depth is at least {} and at most {}", min, max));
+        }
+        let blank_tile = self.board[self.missing_index].index;
+
+        if let Some(table) = DistanceTable::ready(self.m, self.n, self.goal[blank_tile]) {
+            // the table hands back goal cells, which map back to the tiles that belong there
+            let mut tile_for_goal = vec![0; self.goal.len()];
+            for (tile, cell) in self.goal.iter().enumerate() {
+                tile_for_goal[*cell] = tile;
+            }
+            let indices = table.random_at_depth(min, max, rng).ok_or(format!(
+                "no {}x{} board is {}-{} moves from the goal, the deepest is {}",
+                self.m,
+                self.n,
+                min,
+                max,
+                table.get_max_depth()
+            ))?;
+            for (i, goal_cell) in indices.into_iter().enumerate() {
+                let index = tile_for_goal[goal_cell];
+                let tile_type = if index == blank_tile {
+                    self.missing_index = i;
+                    TileType::Missing
+                } else {
+                    TileType::InPlay
+                };
+                self.board[i] = Tile::new(index, tile_type);
+            }
+            return Ok(table.distance(self).unwrap_or(0));
+        }
+
+        let start = self.clone();
+        let mut walk_length = max;
+        for _ in 0..DEPTH_ATTEMPTS {
+            *self = start.clone();
+            self.random_walk(walk_length, rng);
+            match self.ida_star_search(Self::walking_distance, max, &mut SolverStats::default()) {
+                Some(steps) if steps.len() >= min => return Ok(steps.len()),
+                // stepping by the shortfall (or by two) keeps the walk's parity, which the depth
+                // always shares
+                Some(steps) => walk_length += min - steps.len(),
+                None => walk_length = walk_length.saturating_sub(2).max(min),
+            }
+        }
+        *self = start;
+        Err(format!(
+            "no {}x{} board {}-{} moves from the goal turned up in {} tries",
+            self.m, self.n, min, max, DEPTH_ATTEMPTS
+        ))
+    }
+
+    // A random walk that never immediately undoes its previous move.
+    fn random_walk<R: Rng>(&mut self, move_count: usize, rng: &mut R) {
+        let mut previous_index = None;
+        for _ in 0..move_count {
+            let available_to_swap: Vec<usize> = self
+                .get_swappable()
+                .into_iter()
+                .filter(|i| Some(*i) != previous_index)
+                .collect();
+            previous_index = Some(self.missing_index);
+            self.swap(available_to_swap[rng.gen_range(0..available_to_swap.len())]);
+        }
+    }
+
+    // Stochastic hill climbing over blank moves: a random step is kept if it does not lower the
+    // score (or, rarely, even if it does). The last board to reach the best score is kept, so a
+    // metric that plateaus early still gets walked well away from the goal.
+    fn climb<R: Rng>(&mut self, rng: &mut R, score: impl Fn(&NBoard) -> f64) {
+        let mut current_score = score(self);
+        let mut best = self.clone();
+        let mut best_score = current_score;
+        let mut previous_index = None;
+
+        for _ in 0..(CLIMB_STEPS_PER_TILE * self.board.len()) {
+            // stepping straight back would only undo the last accepted move
+            let available_to_swap: Vec<usize> = self
+                .get_swappable()
+                .into_iter()
+                .filter(|i| Some(*i) != previous_index)
+                .collect();
+            let with = available_to_swap[rng.gen_range(0..available_to_swap.len())];
+
+            let from = self.missing_index;
+            self.swap(with);
+            let next_score = score(self);
+            if next_score >= current_score || rng.gen_bool(CLIMB_ESCAPE_PROBABILITY) {
+                current_score = next_score;
+                previous_index = Some(from);
+                if current_score >= best_score {
+                    best_score = current_score;
+                    best = self.clone();
                 }
-                missing_index
+            } else {
+                self.swap(from);
             }
         }
+
+        debug!("climb: best score {}", best_score);
+        *self = best;
     }
 
     pub fn get_swappable(&self) -> Vec<usize> {
-        Self::get_available_to_swap(self.missing_index, self.n)
+        Self::get_available_to_swap(self.missing_index, self.m, self.n)
+    }
+
+    // Spells out a solver's steps, the cells the blank swapped with in turn, starting from here.
+    pub fn moves_from_indices(&self, steps: &[usize]) -> Result<Vec<Move>, String> {
+        let mut blank = self.missing_index;
+        steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                let mv = Move::between(blank, *step, self.m, self.n).ok_or_else(|| {
+                    format!(
+                        "step {} swaps the blank at cell {} with cell {}, which isn't next to it",
+                        i + 1,
+                        blank,
+                        step
+                    )
+                })?;
+                blank = *step;
+                Ok(mv)
+            })
+            .collect()
+    }
+
+    pub fn indices_from_moves(&self, moves: &[Move]) -> Result<Vec<usize>, String> {
+        if self.missing_index >= self.board.len() {
+            return Err("the board has no blank to move yet".to_owned());
+        }
+        let mut blank = self.missing_index;
+        moves
+            .iter()
+            .enumerate()
+            .map(|(i, mv)| {
+                blank = mv.target(blank, self.m, self.n).ok_or_else(|| {
+                    format!(
+                        "move {} ({}) takes the blank at cell {} off the board",
+                        i + 1,
+                        mv,
+                        blank
+                    )
+                })?;
+                Ok(blank)
+            })
+            .collect()
+    }
+
+    // Plays every move, or none of them if any would take the blank off the board.
+    pub fn apply_moves(&mut self, moves: &[Move]) -> Result<(), String> {
+        for index in self.indices_from_moves(moves)? {
+            self.swap(index);
+        }
+        Ok(())
+    }
+
+    // Checks the moves solve this board, see solution.rs.
+    pub fn verify_moves(&self, moves: &[Move]) -> Result<(), String> {
+        solution::verify(self, moves)
+    }
+
+    // The same moves with every detour that returns to an earlier board cut out.
+    pub fn simplify_moves(&self, moves: &[Move]) -> Result<Vec<Move>, String> {
+        solution::simplify(self, moves)
     }
 
+    // Reads the plain text puzzle format, see puzzle_file.rs.
+    pub fn from_text(text: &str, goal: &Goal) -> Result<Self, String> {
+        puzzle_file::read(text, goal)
+    }
+
+    pub fn to_text(&self) -> Result<String, String> {
+        puzzle_file::write(self)
+    }
+
+    #[allow(unused)]
     pub fn to_string_representation(&self) -> String {
         let mut s = String::new();
         for tile in &self.board {
@@ -252,7 +590,26 @@ impl NBoard {
         s
     }
 
-    fn get_available_to_swap(missing_index: usize, n: usize) -> Vec<usize> {
+    pub fn state_key(&self) -> StateKey {
+        if self.board.len() <= PACKED_TILES {
+            StateKey::Packed(
+                self.board
+                    .iter()
+                    .fold(0, |key, tile| (key << 4) | tile.index as u64),
+            )
+        } else if self.board.len() <= 256 {
+            StateKey::Bytes(self.board.iter().map(|tile| tile.index as u8).collect())
+        } else {
+            StateKey::Bytes(
+                self.board
+                    .iter()
+                    .flat_map(|tile| (tile.index as u16).to_le_bytes())
+                    .collect(),
+            )
+        }
+    }
+
+    fn get_available_to_swap(missing_index: usize, m: usize, n: usize) -> Vec<usize> {
         let mut available_to_swap = Vec::default();
 
         // Left
@@ -271,108 +628,297 @@ impl NBoard {
         }
 
         // Above
-        if (missing_index / n) < (n - 1) {
+        if (missing_index / n) < (m - 1) {
             available_to_swap.insert(available_to_swap.len(), missing_index + n);
         }
 
         available_to_swap
     }
 
-    pub fn dfs_solve(&mut self) -> Option<Vec<NBoard>> {
-        let mut visited: std::collections::HashSet<String> = std::collections::HashSet::new();
-        let mut path = Vec::new();
-        if self._dfs_solve(&mut visited, &mut path) {
-            Some(path)
-        } else {
-            None
-        }
+    // Moving the blank straight back to `came_from` only regenerates the parent.
+    pub fn successors(&self, came_from: Option<usize>) -> Vec<usize> {
+        let mut successors = self.get_swappable();
+        successors.retain(|next_index| Some(*next_index) != came_from);
+        successors
     }
 
-    fn _dfs_solve(
-        &mut self,
-        visited: &mut std::collections::HashSet<String>,
-        path: &mut Vec<NBoard>,
-    ) -> bool {
-        if self.check_win() {
-            path.push(self.clone());
-            return true;
-        }
+    // The moves `pruner` allows after reaching this board in `state`, with the state after each.
+    fn pruned_successors(&self, pruner: &MovePruner, state: usize) -> Vec<(usize, usize)> {
+        self.get_swappable()
+            .into_iter()
+            .filter_map(|next_index| {
+                let mv = Move::between(self.missing_index, next_index, self.m, self.n)?;
+                Some((next_index, pruner.next(state, mv)?))
+            })
+            .collect()
+    }
 
-        let current_state = self.to_string_representation();
-        if visited.contains(&current_state) {
-            return false;
+    // Any solution of at most `depth_limit` moves, not necessarily the shortest.
+    #[allow(unused)]
+    pub fn dfs_solve(&self, depth_limit: usize) -> Option<Vec<usize>> {
+        self.depth_limited_search(depth_limit, &mut SolverStats::default())
+    }
+
+    #[allow(unused)]
+    pub fn depth_limited_search(
+        &self,
+        depth_limit: usize,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        run_to_completion(
+            &mut DepthFirstSearch::depth_limited(self, depth_limit),
+            stats,
+        )
+    }
+
+    // The shortest solution, found by depth limited searches one move deeper each time, or None
+    // if it is longer than `max_depth`.
+    #[allow(unused)]
+    pub fn iddfs_solve(&self, max_depth: usize) -> Option<Vec<usize>> {
+        self.iterative_deepening_search(max_depth, &mut SolverStats::default())
+    }
+
+    #[allow(unused)]
+    pub fn iterative_deepening_search(
+        &self,
+        max_depth: usize,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        run_to_completion(
+            &mut DepthFirstSearch::iterative_deepening(self, max_depth),
+            stats,
+        )
+    }
+
+    #[allow(unused)]
+    pub fn a_star_solve(&self) -> Option<Vec<usize>> {
+        self.a_star_solve_with(Self::manhattan_distance)
+    }
+
+    #[allow(unused)]
+    pub fn a_star_solve_with(&self, heuristic: impl Fn(&NBoard) -> usize) -> Option<Vec<usize>> {
+        self.weighted_a_star_solve_with(heuristic, 1.)
+    }
+
+    // f = g + weight * h. The solution is at most `weight` times longer than optimal, and the
+    // heavier the weight the fewer states are expanded before one turns up.
+    #[allow(unused)]
+    pub fn weighted_a_star_solve_with(
+        &self,
+        heuristic: impl Fn(&NBoard) -> usize,
+        weight: f64,
+    ) -> Option<Vec<usize>> {
+        self.weighted_a_star_search(heuristic, weight, &mut SolverStats::default())
+    }
+
+    pub fn weighted_a_star_search(
+        &self,
+        heuristic: impl Fn(&NBoard) -> usize,
+        weight: f64,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        run_to_completion(&mut AStarSearch::new(self, heuristic, weight), stats)
+    }
+
+    // A* on `threads` threads, see parallel_a_star.rs. The stats are every thread's added up, and
+    // each thread's own are kept alongside.
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    pub fn parallel_a_star_search(
+        &self,
+        heuristic: impl Fn(&NBoard) -> usize + Sync,
+        threads: usize,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        let (steps, thread_stats) = parallel_a_star::solve(self, &heuristic, threads);
+        parallel_a_star::record(stats, thread_stats);
+        steps
+    }
+
+    #[allow(unused)]
+    pub fn bidirectional_solve(&self) -> Option<Vec<usize>> {
+        self.bidirectional_solve_with(Self::manhattan_distance)
+    }
+
+    // A* from the scramble and from the goal at once, stopping once the shortest path found through
+    // the two frontiers can no longer be beaten. Without a heuristic it is a bidirectional BFS.
+    #[allow(unused)]
+    pub fn bidirectional_solve_with(
+        &self,
+        heuristic: impl Fn(&NBoard) -> usize,
+    ) -> Option<Vec<usize>> {
+        self.bidirectional_search(heuristic, &mut SolverStats::default())
+    }
+
+    pub fn bidirectional_search(
+        &self,
+        heuristic: impl Fn(&NBoard) -> usize,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        run_to_completion(&mut BidirectionalSearch::new(self, heuristic), stats)
+    }
+
+    // The goal, aiming for this board instead: every heuristic measures the distance to whatever
+    // goal a board carries, so it serves the backward half of a bidirectional search unchanged.
+    fn reversed(&self) -> NBoard {
+        let blank_tile = self.board[self.missing_index].index;
+        let mut reversed = self.clone();
+        for (tile, cell) in self.goal.iter().enumerate() {
+            let tile_type = match tile == blank_tile {
+                true => TileType::Missing,
+                false => TileType::InPlay,
+            };
+            reversed.board[*cell] = Tile::new(tile, tile_type);
+        }
+        let mut goal = vec![0; self.board.len()];
+        for (cell, tile) in self.board.iter().enumerate() {
+            goal[tile.index] = cell;
         }
-        visited.insert(current_state.clone());
+        reversed.goal = goal.into();
+        reversed.missing_index = self.goal[blank_tile];
+        reversed
+    }
 
-        for &next_index in &Self::get_available_to_swap(self.missing_index, self.n) {
-            self.swap(next_index);
-            if self._dfs_solve(visited, path) {
-                path.push(self.clone());
-                return true;
-            }
-            self.swap(next_index); // backtrack
+    #[allow(unused)]
+    pub fn ara_star_solve_with(
+        &self,
+        heuristic: impl Fn(&NBoard) -> usize,
+        initial_weight: f64,
+        budget_ms: i64,
+    ) -> Vec<AnytimeSolution> {
+        self.ara_star_search(
+            heuristic,
+            initial_weight,
+            budget_ms,
+            &mut SolverStats::default(),
+        )
+    }
+
+    // Anytime Repairing A* (Likhachev, Gordon & Thrun): weighted A* from `initial_weight` that keeps
+    // its search tree between passes, lowering the weight by ARA_WEIGHT_STEP after every solution
+    // until the weight reaches 1 or `budget_ms` runs out. Returns each improvement with its proven
+    // bound. The budget only applies once a solution is known: the first pass runs to the end,
+    // however long it takes, so there is always something to return.
+    pub fn ara_star_search(
+        &self,
+        heuristic: impl Fn(&NBoard) -> usize,
+        initial_weight: f64,
+        budget_ms: i64,
+        stats: &mut SolverStats,
+    ) -> Vec<AnytimeSolution> {
+        let mut search = AraStarSearch::new(self, heuristic, initial_weight, budget_ms);
+        run_to_completion(&mut search, stats);
+        search.solutions
+    }
+
+    fn anytime_path(nodes: &[AnytimeNode], goal: usize) -> Vec<usize> {
+        let mut steps = Vec::default();
+        let mut id = goal;
+        while let Some((parent, swappable_index)) = nodes[id].parent {
+            steps.push(swappable_index);
+            id = parent;
         }
-        false
+        steps.reverse();
+        steps
     }
 
-    pub fn a_star_solve(&self) -> Option<Vec<usize>> {
-        let mut visited: HashSet<String> = HashSet::new();
-        let mut heap = BinaryHeap::new();
+    #[allow(unused)]
+    pub fn ida_star_solve(&self) -> Option<Vec<usize>> {
+        self.ida_star_solve_with(Self::manhattan_distance)
+    }
 
-        heap.push(State {
-            cost: 0,
-            board: self.clone(),
-            steps: Vec::new(),
-        });
+    #[allow(unused)]
+    pub fn ida_star_solve_with(&self, heuristic: impl Fn(&NBoard) -> usize) -> Option<Vec<usize>> {
+        self.ida_star_search(heuristic, usize::MAX, &mut SolverStats::default())
+    }
 
-        while let Some(State { cost, board, steps }) = heap.pop() {
-            if board.check_win() {
-                return Some(steps);
-            }
+    // IDA* that gives up once the bound would pass `max_bound`, i.e. when no solution of at most
+    // that many moves exists.
+    pub fn ida_star_search(
+        &self,
+        heuristic: impl Fn(&NBoard) -> usize,
+        max_bound: usize,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        run_to_completion(&mut IdaStarSearch::new(self, heuristic, max_bound), stats)
+    }
 
-            let state_str = board.to_string();
-            if visited.contains(&state_str) {
-                continue;
-            }
-            visited.insert(state_str);
+    // A valid but long solution for a board of any size, see layered.rs.
+    #[allow(unused)]
+    pub fn layered_solve(&self) -> Option<Vec<usize>> {
+        self.layered_search(&mut SolverStats::default())
+    }
 
-            for swappable_index in board.get_swappable() {
-                let mut new_board = board.clone();
-                new_board.swap(swappable_index);
+    pub fn layered_search(&self, stats: &mut SolverStats) -> Option<Vec<usize>> {
+        layered::solve(self, stats)
+    }
 
-                let mut new_steps = steps.clone();
-                new_steps.push(swappable_index);
+    // Exact number of moves from the goal, for boards small enough to have a distance table (and,
+    // on the web, once it is built).
+    pub fn optimal_distance(&self) -> Option<usize> {
+        DistanceTable::ready(self.m, self.n, self.blank_goal()?)?.distance(self)
+    }
 
-                heap.push(State {
-                    cost: new_steps.len() + new_board.manhattan_distance(),
-                    board: new_board,
-                    steps: new_steps,
-                });
-            }
+    // Walks the distance table downhill: every step is to a neighbour one move closer, so the
+    // solution is optimal and each step costs a lookup per neighbour.
+    pub fn distance_table_search(&self, stats: &mut SolverStats) -> Option<Vec<usize>> {
+        let table = DistanceTable::ready(self.m, self.n, self.blank_goal()?)?;
+        let mut distance = table.distance(self)?;
+        let mut board = self.clone();
+        let mut steps = Vec::default();
+        while distance > 0 {
+            stats.expanded += 1;
+            let swappable = board.get_swappable();
+            stats.generated += swappable.len();
+            let step = swappable.into_iter().find(|step| {
+                let mut next = board.clone();
+                next.swap(*step);
+                table.distance(&next) == Some(distance - 1)
+            })?;
+            board.swap(step);
+            steps.push(step);
+            distance -= 1;
+        }
+        stats.record_frontier(steps.len(), self.search_node_bytes(steps.len()));
+        Some(steps)
+    }
+
+    // Heap bytes behind a board a search holds on to, plus a path of `depth` moves.
+    pub fn search_node_bytes(&self, depth: usize) -> usize {
+        (2 * self.board.len() * std::mem::size_of::<Tile>())
+            + (depth * std::mem::size_of::<usize>())
+    }
+
+    pub fn state_key_bytes(&self) -> usize {
+        match self.board.len() {
+            tiles if tiles > PACKED_TILES => std::mem::size_of::<StateKey>() + tiles,
+            _ => std::mem::size_of::<StateKey>(),
         }
-        None
     }
 
     pub fn get_missing_index(&self) -> usize {
         self.missing_index
     }
 
-    fn get_n_from_board(board: &Vec<Tile>) -> usize {
-        (board.len() as f64).sqrt() as usize
+    pub fn get_m(&self) -> usize {
+        self.m
+    }
+
+    pub fn get_n(&self) -> usize {
+        self.n
     }
 
     fn neither_are_missing(t1: &Tile, t2: &Tile) -> bool {
         (t1.tile_type != TileType::Missing) && (t2.tile_type != TileType::Missing)
     }
 
-    fn manhattan_distance(&self) -> usize {
+    pub fn manhattan_distance(&self) -> usize {
         let mut distance = 0;
         for (i, tile) in self.board.iter().enumerate() {
             if tile.tile_type == TileType::Missing {
                 continue;
             }
-            let final_x = tile.index % self.n;
-            let final_y = tile.index / self.n;
+            let final_x = self.goal[tile.index] % self.n;
+            let final_y = self.goal[tile.index] / self.n;
             let current_x = i % self.n;
             let current_y = i / self.n;
             distance += (final_x as isize - current_x as isize).abs()
@@ -381,15 +927,114 @@ impl NBoard {
         distance as usize
     }
 
-    fn count_inversions(board: &Vec<Tile>) -> usize {
+    pub fn euclidean_distance(&self) -> f64 {
+        let mut distance = 0.;
+        for (i, tile) in self.board.iter().enumerate() {
+            if tile.tile_type == TileType::Missing {
+                continue;
+            }
+            let dx = (self.goal[tile.index] % self.n) as f64 - (i % self.n) as f64;
+            let dy = (self.goal[tile.index] / self.n) as f64 - (i / self.n) as f64;
+            distance += ((dx * dx) + (dy * dy)).sqrt();
+        }
+        distance
+    }
+
+    pub fn tiles_out(&self) -> usize {
+        self.board
+            .iter()
+            .enumerate()
+            .filter(|(i, tile)| tile.tile_type != TileType::Missing && self.goal[tile.index] != *i)
+            .count()
+    }
+
+    // Manhattan distance plus two moves for every tile that has to step out of its goal row or
+    // column so the others sharing it can pass. Counting the fewest tiles to lift out of a line,
+    // rather than every reversed pair, keeps the estimate admissible.
+    pub fn linear_conflict(&self) -> usize {
+        let mut conflicts = 0;
+        for row in 0..self.m {
+            let mut goals = Vec::default();
+            for column in 0..self.n {
+                let tile = &self.board[(row * self.n) + column];
+                let goal = self.goal[tile.index];
+                if tile.tile_type != TileType::Missing && goal / self.n == row {
+                    goals.push(goal % self.n);
+                }
+            }
+            conflicts += Self::tiles_out_of_order(&goals);
+        }
+        for column in 0..self.n {
+            let mut goals = Vec::default();
+            for row in 0..self.m {
+                let tile = &self.board[(row * self.n) + column];
+                let goal = self.goal[tile.index];
+                if tile.tile_type != TileType::Missing && goal % self.n == column {
+                    goals.push(goal / self.n);
+                }
+            }
+            conflicts += Self::tiles_out_of_order(&goals);
+        }
+        self.manhattan_distance() + (2 * conflicts)
+    }
+
+    // Sum of the vertical and horizontal walking distances. Boards wider or taller than the cached
+    // tables allow fall back to linear_conflict.
+    pub fn walking_distance(&self) -> usize {
+        let blank_goal = match self.board.get(self.missing_index) {
+            Some(tile)
+                if WalkingDistanceTable::supports(self.m, self.n)
+                    && WalkingDistanceTable::supports(self.n, self.m) =>
+            {
+                self.goal[tile.index]
+            }
+            _ => return self.linear_conflict(),
+        };
+
+        // rows are m lines of n tiles, columns n lines of m tiles
+        let rows = WalkingDistanceTable::get(self.m, self.n, blank_goal / self.n);
+        let columns = WalkingDistanceTable::get(self.n, self.m, blank_goal % self.n);
+
+        let mut row_counts = vec![0u8; self.m * self.m];
+        let mut column_counts = vec![0u8; self.n * self.n];
+        for (i, tile) in self.board.iter().enumerate() {
+            if tile.tile_type == TileType::Missing {
+                continue;
+            }
+            let goal = self.goal[tile.index];
+            row_counts[((i / self.n) * self.m) + (goal / self.n)] += 1;
+            column_counts[((i % self.n) * self.n) + (goal % self.n)] += 1;
+        }
+
+        rows.distance(&row_counts, self.missing_index / self.n)
+            + columns.distance(&column_counts, self.missing_index % self.n)
+    }
+
+    // Fewest tiles that must leave a line for the rest to already be in goal order, i.e. the line
+    // length minus its longest increasing run of goal positions.
+    fn tiles_out_of_order(goals: &[usize]) -> usize {
+        let mut longest = vec![1; goals.len()];
+        for i in 0..goals.len() {
+            for j in 0..i {
+                if goals[j] < goals[i] {
+                    longest[i] = longest[i].max(longest[j] + 1);
+                }
+            }
+        }
+        goals.len() - longest.iter().copied().max().unwrap_or(0)
+    }
+
+    // Tiles are compared by their goal cells, which for any goal reduces to counting inversions
+    // against the row major one.
+    fn count_inversions(board: &Vec<Tile>, goal: &[usize]) -> usize {
         let mut count = 0;
-        let n = board.len(); // Assuming board is a flat array representing N x N board
+        let n = board.len(); // Assuming board is a flat array representing M x N board
 
         for i in 0..n {
             for j in (i + 1)..n {
                 let t1 = &board[i];
                 let t2 = &board[j];
-                if Self::neither_are_missing(t1, t2) && (t1.index > t2.index) {
+                if Self::neither_are_missing(t1, t2) && (goal[t1.index] > goal[t2.index]) {
                     count += 1;
                 }
             }
@@ -401,40 +1046,1179 @@ impl NBoard {
         index / n
     }
 
+    // Settled by parity alone, whatever the size, blank and goal.
     pub fn solvable(&self) -> bool {
         if self.check_win() {
             return true;
         }
-        if self.n == 2 || self.n == 3 {
-            let mut check = self.clone();
-            if let Some(solution) = check.a_star_solve() {
-                return true;
+        // nothing can move until a tile is punched out
+        if self.missing_index >= self.board.len() {
+            return false;
+        }
+        Self::is_solvable(&self.board, self.missing_index, self.n, &self.goal)
+    }
+
+    // Only the width matters: a vertical blank move carries a tile past n - 1 others, which flips the
+    // inversion parity exactly when n is even, however many rows there are.
+    fn is_solvable(board: &Vec<Tile>, missing_index: usize, n: usize, goal: &[usize]) -> bool {
+        let inversion_count = Self::count_inversions(board, goal);
+        if !is_even(n) {
+            return is_even(inversion_count);
+        } else {
+            // every vertical blank move flips the inversion parity, so count how many rows
+            // the blank sits away from where the missing tile belongs
+            let missing_index_row = Self::get_row_from_index(missing_index, n);
+            let missing_goal_row = Self::get_row_from_index(goal[board[missing_index].index], n);
+            let row_distance = (missing_index_row as isize - missing_goal_row as isize).abs();
+            return is_even(inversion_count) == is_even(row_distance as usize);
+        }
+    }
+
+    // Why no sequence of moves reaches the goal from here, or None if one does.
+    pub fn unsolvable_reason(&self) -> Option<String> {
+        if self.solvable() {
+            return None;
+        }
+        if self.missing_index >= self.board.len() {
+            return Some("it has no blank to move".to_owned());
+        }
+        let inversion_count = Self::count_inversions(&self.board, &self.goal);
+        if !is_even(self.n) {
+            return Some(format!(
+                "it has {} inversions, an odd number, and on a board {} wide every move keeps \
+                 their count odd or even",
+                inversion_count, self.n
+            ));
+        }
+        let missing_index_row = Self::get_row_from_index(self.missing_index, self.n);
+        let missing_goal_row =
+            Self::get_row_from_index(self.goal[self.board[self.missing_index].index], self.n);
+        Some(format!(
+            "it has {} inversions and the blank is {} rows from its goal row, an odd total, and \
+             on a board {} wide every move keeps that total odd or even",
+            inversion_count,
+            (missing_index_row as isize - missing_goal_row as isize).abs(),
+            self.n
+        ))
+    }
+
+    fn initialize_board(board: &mut Vec<Tile>, goal: &[usize]) {
+        *board = vec![Tile::default(); goal.len()];
+        for (tile, cell) in goal.iter().enumerate() {
+            board[*cell] = Tile::new(tile, TileType::InPlay);
+        }
+    }
+}
+
+// Weighted A*, one expansion per call to expand.
+pub struct AStarSearch<H> {
+    heuristic: H,
+    weight: f64,
+    visited: HashSet<StateKey>,
+    heap: BinaryHeap<State>,
+}
+
+impl<H: Fn(&NBoard) -> usize> AStarSearch<H> {
+    pub fn new(board: &NBoard, heuristic: H, weight: f64) -> Self {
+        let mut heap = BinaryHeap::new();
+        heap.push(State {
+            cost: 0,
+            board: board.clone(),
+            steps: Vec::new(),
+            came_from: None,
+        });
+        Self {
+            heuristic,
+            weight,
+            visited: HashSet::new(),
+            heap,
+        }
+    }
+}
+
+impl<H: Fn(&NBoard) -> usize> Expand for AStarSearch<H> {
+    fn expand(&mut self, stats: &mut SolverStats) -> SearchStatus {
+        let Some(State {
+            board,
+            steps,
+            came_from,
+            ..
+        }) = self.heap.pop()
+        else {
+            return SearchStatus::Failed;
+        };
+        if board.check_win() {
+            return SearchStatus::Solved(steps);
+        }
+
+        if !self.visited.insert(board.state_key()) {
+            return SearchStatus::Running;
+        }
+
+        stats.expanded += 1;
+        stats.record_frontier(
+            self.heap.len(),
+            (self.heap.len()
+                * (std::mem::size_of::<State>() + board.search_node_bytes(steps.len())))
+                + (self.visited.len() * board.state_key_bytes()),
+        );
+        for swappable_index in board.successors(came_from) {
+            let mut new_board = board.clone();
+            new_board.swap(swappable_index);
+            stats.generated += 1;
+
+            let mut new_steps = steps.clone();
+            new_steps.push(swappable_index);
+
+            self.heap.push(State {
+                cost: weighted_cost(new_steps.len(), (self.heuristic)(&new_board), self.weight),
+                board: new_board,
+                steps: new_steps,
+                came_from: Some(board.missing_index),
+            });
+        }
+        SearchStatus::Running
+    }
+}
+
+// ARA*, one expansion per call to expand. Passes end on their own call, which records the solution
+// and lowers the weight for the next pass.
+pub struct AraStarSearch<H> {
+    heuristic: H,
+    weight: f64,
+    deadline: chrono::DateTime<chrono::Utc>,
+    nodes: Vec<AnytimeNode>,
+    ids: HashMap<StateKey, usize>,
+    open: BinaryHeap<Reverse<(usize, usize, usize)>>,
+    incons: Vec<usize>,
+    goal_id: Option<usize>,
+    // expansions since the goal was first reached, to space out looking at the clock
+    expansions: usize,
+    solutions: Vec<AnytimeSolution>,
+}
+
+impl<H: Fn(&NBoard) -> usize> AraStarSearch<H> {
+    pub fn new(board: &NBoard, heuristic: H, initial_weight: f64, budget_ms: i64) -> Self {
+        let weight = initial_weight.max(1.);
+        let h = heuristic(board);
+        let mut ids = HashMap::new();
+        ids.insert(board.state_key(), 0);
+        // an unsolvable board is given up on straight away rather than searched exhaustively
+        let mut open = BinaryHeap::new();
+        if board.solvable() {
+            open.push(Reverse((weighted_cost(0, h, weight), 0, 0)));
+        }
+        Self {
+            heuristic,
+            weight,
+            deadline: chrono::Utc::now() + chrono::Duration::milliseconds(budget_ms),
+            nodes: vec![AnytimeNode {
+                board: board.clone(),
+                g: 0,
+                h,
+                parent: None,
+                closed: false,
+                inconsistent: false,
+            }],
+            ids,
+            open,
+            incons: Vec::default(),
+            goal_id: board.check_win().then_some(0),
+            expansions: 0,
+            solutions: Vec::default(),
+        }
+    }
+
+    // Records what the pass found, then either finishes or starts the next pass. `finished` is false
+    // if the deadline cut the pass short.
+    fn end_pass(&mut self, finished: bool) -> SearchStatus {
+        // open ran dry without ever reaching the goal
+        let Some(goal) = self.goal_id else {
+            return SearchStatus::Failed;
+        };
+
+        // every optimal path still runs through an open or inconsistent node, so the smallest
+        // unweighted f among them is a lower bound on the optimal length
+        let nodes = &self.nodes;
+        let lower_bound = self
+            .open
+            .iter()
+            .map(|Reverse((_, _, id))| *id)
+            .chain(self.incons.iter().copied())
+            .filter(|id| !nodes[*id].closed || nodes[*id].inconsistent)
+            .map(|id| nodes[id].g + nodes[id].h)
+            .min()
+            .unwrap_or(nodes[goal].g);
+        let ratio = nodes[goal].g as f64 / lower_bound.max(1) as f64;
+        let bound = if finished {
+            ratio.min(self.weight)
+        } else {
+            ratio
+        }
+        .max(1.);
+
+        let steps = NBoard::anytime_path(nodes, goal);
+        let improved = match self.solutions.last() {
+            Some(last) => steps.len() < last.steps.len() || bound < last.bound,
+            None => true,
+        };
+        if improved {
+            debug!("ara*: {} moves within {:.2} of optimal", steps.len(), bound);
+            self.solutions.push(AnytimeSolution { steps, bound });
+        }
+
+        if !finished || self.weight <= 1. || chrono::Utc::now() > self.deadline {
+            return match self.solutions.last() {
+                Some(best) => SearchStatus::Solved(best.steps.clone()),
+                None => SearchStatus::Failed,
+            };
+        }
+
+        // lower the weight, fold the inconsistent nodes back into open and forget what was
+        // closed, then repair the tree under the new weight
+        self.weight = (self.weight - ARA_WEIGHT_STEP).max(1.);
+        let mut reopened: Vec<usize> = self
+            .open
+            .drain()
+            .map(|Reverse((_, _, id))| id)
+            .filter(|id| !self.nodes[*id].closed)
+            .collect();
+        reopened.append(&mut self.incons);
+        for node in self.nodes.iter_mut() {
+            node.closed = false;
+            node.inconsistent = false;
+        }
+        for id in reopened {
+            let node = &self.nodes[id];
+            let cost = weighted_cost(node.g, node.h, self.weight);
+            self.open.push(Reverse((cost, node.g, id)));
+        }
+        SearchStatus::Running
+    }
+}
+
+impl<H: Fn(&NBoard) -> usize> Expand for AraStarSearch<H> {
+    fn expand(&mut self, stats: &mut SolverStats) -> SearchStatus {
+        // entries are left behind when a node's g improves or it gets closed
+        while let Some(Reverse((_, g, id))) = self.open.peek() {
+            if self.nodes[*id].closed || self.nodes[*id].g != *g {
+                self.open.pop();
             } else {
+                break;
+            }
+        }
+        let Some(Reverse((f, _, id))) = self.open.pop() else {
+            return self.end_pass(true);
+        };
+        if let Some(goal) = self.goal_id {
+            if weighted_cost(self.nodes[goal].g, 0, self.weight) <= f {
+                self.open.push(Reverse((f, self.nodes[id].g, id)));
+                return self.end_pass(true);
+            }
+            self.expansions += 1;
+            if self.expansions % ARA_CLOCK_INTERVAL == 0 && chrono::Utc::now() > self.deadline {
+                self.open.push(Reverse((f, self.nodes[id].g, id)));
+                return self.end_pass(false);
+            }
+        }
+
+        let nodes = &mut self.nodes;
+        nodes[id].closed = true;
+        stats.expanded += 1;
+        stats.record_frontier(
+            self.open.len(),
+            (nodes.len()
+                * (std::mem::size_of::<AnytimeNode>() + nodes[id].board.search_node_bytes(0)))
+                + (self.ids.len()
+                    * (nodes[id].board.state_key_bytes() + std::mem::size_of::<usize>()))
+                + (self.open.len() * std::mem::size_of::<Reverse<(usize, usize, usize)>>()),
+        );
+        let g = nodes[id].g + 1;
+        let came_from = nodes[id]
+            .parent
+            .map(|(parent, _)| nodes[parent].board.missing_index);
+        for swappable_index in nodes[id].board.successors(came_from) {
+            let mut new_board = nodes[id].board.clone();
+            new_board.swap(swappable_index);
+            stats.generated += 1;
+
+            let child = match self.ids.get(&new_board.state_key()) {
+                Some(child) => *child,
+                None => {
+                    self.ids.insert(new_board.state_key(), nodes.len());
+                    nodes.push(AnytimeNode {
+                        h: (self.heuristic)(&new_board),
+                        board: new_board,
+                        g: usize::MAX,
+                        parent: None,
+                        closed: false,
+                        inconsistent: false,
+                    });
+                    nodes.len() - 1
+                }
+            };
+
+            if g < nodes[child].g {
+                nodes[child].g = g;
+                nodes[child].parent = Some((id, swappable_index));
+                if nodes[child].board.check_win() {
+                    self.goal_id = Some(child);
+                }
+                if !nodes[child].closed {
+                    let cost = weighted_cost(g, nodes[child].h, self.weight);
+                    self.open.push(Reverse((cost, g, child)));
+                } else if !nodes[child].inconsistent {
+                    nodes[child].inconsistent = true;
+                    self.incons.push(child);
+                }
+            }
+        }
+        SearchStatus::Running
+    }
+}
+
+struct Frontier {
+    f: usize,
+    g: usize,
+    key: StateKey,
+    board: NBoard,
+}
+
+// Lowest f first, and the deepest of those, which is likeliest to be close to the other side.
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.f.cmp(&self.f).then(self.g.cmp(&other.g))
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Frontier {
+    fn eq(&self, other: &Self) -> bool {
+        (self.f, self.g) == (other.f, other.g)
+    }
+}
+
+impl Eq for Frontier {}
+
+struct Reached {
+    g: usize,
+    parent: Option<StateKey>,
+    // where the blank is in this state
+    blank: usize,
+    closed: bool,
+}
+
+// One direction of a bidirectional search. Entries left in `open` after their state was closed or
+// reached more cheaply are skipped rather than removed.
+#[derive(Default)]
+struct Side {
+    open: BinaryHeap<Frontier>,
+    reached: HashMap<StateKey, Reached>,
+    // how many open states there are at each g
+    open_g: BTreeMap<usize, usize>,
+}
+
+impl Side {
+    fn offer(&mut self, key: &StateKey, g: usize, parent: Option<StateKey>, blank: usize) -> bool {
+        if let Some(reached) = self.reached.get(key) {
+            if reached.g <= g {
                 return false;
             }
+            if !reached.closed {
+                self.forget_open(reached.g);
+            }
         }
+        self.reached.insert(
+            key.clone(),
+            Reached {
+                g,
+                parent,
+                blank,
+                closed: false,
+            },
+        );
+        *self.open_g.entry(g).or_insert(0) += 1;
+        true
+    }
 
-        Self::is_solvable(&self.board, self.missing_index, self.n)
+    fn forget_open(&mut self, g: usize) {
+        if let Some(count) = self.open_g.get_mut(&g) {
+            *count -= 1;
+            if *count == 0 {
+                self.open_g.remove(&g);
+            }
+        }
     }
 
-    fn is_solvable(board: &Vec<Tile>, missing_index: usize, n: usize) -> bool {
-        let inversion_count = Self::count_inversions(board);
-        if !is_even(n) {
-            return is_even(inversion_count);
-        } else {
-            let missing_index_row = n - Self::get_row_from_index(missing_index, n);
-            return is_even(inversion_count) == is_even(missing_index_row);
+    fn prune(&mut self) {
+        while let Some(top) = self.open.peek() {
+            match self.reached.get(&top.key) {
+                Some(reached) if !reached.closed && reached.g == top.g => return,
+                _ => {
+                    self.open.pop();
+                }
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<Frontier> {
+        self.prune();
+        let node = self.open.pop()?;
+        if let Some(reached) = self.reached.get_mut(&node.key) {
+            reached.closed = true;
+        }
+        self.forget_open(node.g);
+        Some(node)
+    }
+
+    fn bytes(&self, board: &NBoard) -> usize {
+        (self.open.len() * (std::mem::size_of::<Frontier>() + board.search_node_bytes(0)))
+            + (self.reached.len() * (std::mem::size_of::<Reached>() + board.state_key_bytes()))
+    }
+}
+
+// Bidirectional A* that expands one state per call, from whichever side has the smaller frontier.
+// Every path not yet found runs through both frontiers, so none is shorter than the largest f on
+// either side, nor than the two smallest g plus the move between them; once the best meeting found
+// is no longer than that, it is optimal.
+pub struct BidirectionalSearch<H> {
+    heuristic: H,
+    forward: Side,
+    backward: Side,
+    // length of the shortest path found so far, and the state where its two halves meet
+    best: Option<(usize, StateKey)>,
+    exhausted: bool,
+}
+
+impl<H: Fn(&NBoard) -> usize> BidirectionalSearch<H> {
+    pub fn new(board: &NBoard, heuristic: H) -> Self {
+        let mut search = Self {
+            heuristic,
+            forward: Side::default(),
+            backward: Side::default(),
+            best: None,
+            exhausted: !board.solvable(),
+        };
+        if search.exhausted {
+            return search;
+        }
+        let goal = board.reversed();
+        let (start_key, goal_key) = (board.state_key(), goal.state_key());
+        for (side, root, key) in [
+            (&mut search.forward, board.clone(), start_key.clone()),
+            (&mut search.backward, goal, goal_key.clone()),
+        ] {
+            side.offer(&key, 0, None, root.missing_index);
+            side.open.push(Frontier {
+                f: (search.heuristic)(&root),
+                g: 0,
+                key,
+                board: root,
+            });
+        }
+        if start_key == goal_key {
+            search.best = Some((0, start_key));
+        }
+        search
+    }
+
+    fn lower_bound(&self) -> usize {
+        let f_min = |side: &Side| side.open.peek().map_or(usize::MAX, |top| top.f);
+        let g_min = |side: &Side| side.open_g.keys().next().copied().unwrap_or(usize::MAX);
+        f_min(&self.forward).max(f_min(&self.backward)).max(
+            g_min(&self.forward)
+                .saturating_add(g_min(&self.backward))
+                .saturating_add(1),
+        )
+    }
+
+    // The blank's cells along the forward half up to the meeting state, then along the backward
+    // half from there to the goal.
+    fn path(&self, meeting: &StateKey) -> Vec<usize> {
+        let mut steps = Vec::default();
+        let mut key = meeting;
+        while let Some(reached) = self.forward.reached.get(key) {
+            let Some(parent) = &reached.parent else {
+                break;
+            };
+            steps.push(reached.blank);
+            key = parent;
+        }
+        steps.reverse();
+
+        let mut key = meeting;
+        while let Some(parent) = self
+            .backward
+            .reached
+            .get(key)
+            .and_then(|reached| reached.parent.as_ref())
+        {
+            steps.push(self.backward.reached[parent].blank);
+            key = parent;
+        }
+        steps
+    }
+}
+
+impl<H: Fn(&NBoard) -> usize> Expand for BidirectionalSearch<H> {
+    fn expand(&mut self, stats: &mut SolverStats) -> SearchStatus {
+        if self.exhausted {
+            return SearchStatus::Failed;
+        }
+        self.forward.prune();
+        self.backward.prune();
+        if let Some((length, meeting)) = &self.best {
+            if *length <= self.lower_bound() {
+                return SearchStatus::Solved(self.path(meeting));
+            }
+        }
+
+        let forward = self.forward.open.len() <= self.backward.open.len();
+        let (side, other) = match forward {
+            true => (&mut self.forward, &self.backward),
+            false => (&mut self.backward, &self.forward),
+        };
+        let Some(Frontier { g, key, board, .. }) = side.pop() else {
+            self.exhausted = true;
+            return SearchStatus::Failed;
+        };
+
+        stats.expanded += 1;
+        let came_from = side.reached[&key]
+            .parent
+            .as_ref()
+            .map(|parent| side.reached[parent].blank);
+        for swappable_index in board.successors(came_from) {
+            let mut new_board = board.clone();
+            new_board.swap(swappable_index);
+            stats.generated += 1;
+
+            let new_key = new_board.state_key();
+            if !side.offer(&new_key, g + 1, Some(key.clone()), swappable_index) {
+                continue;
+            }
+            if let Some(reached) = other.reached.get(&new_key) {
+                let length = g + 1 + reached.g;
+                if !matches!(&self.best, Some((best, _)) if *best <= length) {
+                    self.best = Some((length, new_key.clone()));
+                }
+            }
+            side.open.push(Frontier {
+                f: g + 1 + (self.heuristic)(&new_board),
+                g: g + 1,
+                key: new_key,
+                board: new_board,
+            });
+        }
+        stats.record_frontier(
+            self.forward.open.len() + self.backward.open.len(),
+            self.forward.bytes(&board) + self.backward.bytes(&board),
+        );
+        SearchStatus::Running
+    }
+}
+
+struct DepthFrame {
+    // where the blank was before the move that led here; None at the root
+    came_from: Option<usize>,
+    // the blank's next cells, with the pruner's state after moving there
+    children: Vec<(usize, usize)>,
+    next: usize,
+}
+
+// IDA* on an explicit stack, so it can stop between any two nodes: each call to expand either visits
+// a child, backtracks, or starts the next iteration with a raised bound.
+pub struct IdaStarSearch<H> {
+    heuristic: H,
+    // built on the first expansion, since a long pruner takes a moment
+    pruner_length: usize,
+    pruner: Option<Arc<MovePruner>>,
+    board: NBoard,
+    steps: Vec<usize>,
+    stack: Vec<DepthFrame>,
+    bound: usize,
+    max_bound: usize,
+    min_exceeded: usize,
+    exhausted: bool,
+}
+
+impl<H: Fn(&NBoard) -> usize> IdaStarSearch<H> {
+    pub fn new(board: &NBoard, heuristic: H, max_bound: usize) -> Self {
+        let bound = heuristic(board);
+        Self {
+            // iterative deepening never exhausts an unsolvable board's bound, so bail early
+            exhausted: !board.solvable() || bound > max_bound,
+            heuristic,
+            pruner_length: INVERSE_LENGTH,
+            pruner: None,
+            board: board.clone(),
+            steps: Vec::new(),
+            stack: Vec::new(),
+            bound,
+            max_bound,
+            min_exceeded: usize::MAX,
+        }
+    }
+
+    // Swaps in a longer pruner than the default, which only cuts moves straight back.
+    pub fn with_pruner(mut self, max_length: usize) -> Self {
+        self.pruner_length = max_length;
+        self
+    }
+
+    fn visit(
+        &mut self,
+        came_from: Option<usize>,
+        state: usize,
+        stats: &mut SolverStats,
+    ) -> SearchStatus {
+        let f = self.steps.len() + (self.heuristic)(&self.board);
+        if f > self.bound {
+            self.min_exceeded = self.min_exceeded.min(f);
+            if let Some(came_from) = came_from {
+                self.board.swap(came_from);
+                self.steps.pop();
+            }
+            return SearchStatus::Running;
+        }
+        if self.board.check_win() {
+            return SearchStatus::Solved(self.steps.clone());
+        }
+
+        stats.expanded += 1;
+        stats.record_frontier(
+            self.steps.len(),
+            std::mem::size_of::<NBoard>() + self.board.search_node_bytes(self.steps.len()),
+        );
+        let pruner = self
+            .pruner
+            .get_or_insert_with(|| MovePruner::get(self.pruner_length));
+        let children = self.board.pruned_successors(pruner, state);
+        self.stack.push(DepthFrame {
+            came_from,
+            children,
+            next: 0,
+        });
+        SearchStatus::Running
+    }
+}
+
+impl<H: Fn(&NBoard) -> usize> Expand for IdaStarSearch<H> {
+    fn expand(&mut self, stats: &mut SolverStats) -> SearchStatus {
+        if self.exhausted {
+            return SearchStatus::Failed;
+        }
+        let Some(frame) = self.stack.last_mut() else {
+            return self.visit(None, MovePruner::START, stats);
+        };
+
+        if let Some((next_index, state)) = frame.children.get(frame.next).copied() {
+            frame.next += 1;
+            let came_from = self.board.get_missing_index();
+            self.board.swap(next_index);
+            self.steps.push(next_index);
+            stats.generated += 1;
+            return self.visit(Some(came_from), state, stats);
+        }
+
+        // backtrack
+        if let Some(came_from) = self.stack.pop().and_then(|frame| frame.came_from) {
+            self.board.swap(came_from);
+            self.steps.pop();
+            return SearchStatus::Running;
+        }
+
+        // the root is done with, so this bound holds no solution
+        if self.min_exceeded == usize::MAX || self.min_exceeded > self.max_bound {
+            self.exhausted = true;
+            return SearchStatus::Failed;
+        }
+        debug!(
+            "ida*: raising bound {} -> {}",
+            self.bound, self.min_exceeded
+        );
+        self.bound = self.min_exceeded;
+        self.min_exceeded = usize::MAX;
+        SearchStatus::Running
+    }
+}
+
+// Depth first search on the same kind of explicit stack as IDA*, cut off at `limit` moves. Iterative
+// deepening starts the limit at 0 and raises it by one each time the root is exhausted, up to
+// `max_limit`, so the first solution it finds is a shortest one.
+pub struct DepthFirstSearch {
+    pruner_length: usize,
+    pruner: Option<Arc<MovePruner>>,
+    board: NBoard,
+    steps: Vec<usize>,
+    stack: Vec<DepthFrame>,
+    limit: usize,
+    max_limit: usize,
+    // whether any node was cut off by the limit, without which a deeper pass finds nothing new
+    cut_off: bool,
+    exhausted: bool,
+}
+
+impl DepthFirstSearch {
+    pub fn depth_limited(board: &NBoard, limit: usize) -> Self {
+        Self::new(board, limit, limit)
+    }
+
+    pub fn iterative_deepening(board: &NBoard, max_limit: usize) -> Self {
+        Self::new(board, 0, max_limit)
+    }
+
+    fn new(board: &NBoard, limit: usize, max_limit: usize) -> Self {
+        Self {
+            exhausted: !board.solvable(),
+            pruner_length: INVERSE_LENGTH,
+            pruner: None,
+            board: board.clone(),
+            steps: Vec::new(),
+            stack: Vec::new(),
+            limit,
+            max_limit,
+            cut_off: false,
         }
     }
 
-    fn initialize_board(board: &mut Vec<Tile>, n: usize) {
-        let tile_count = n * n;
-        for i in 0..tile_count {
-            board.insert(i, Tile::new(i, TileType::InPlay));
+    pub fn with_pruner(mut self, max_length: usize) -> Self {
+        self.pruner_length = max_length;
+        self
+    }
+
+    fn visit(
+        &mut self,
+        came_from: Option<usize>,
+        state: usize,
+        stats: &mut SolverStats,
+    ) -> SearchStatus {
+        if self.board.check_win() {
+            return SearchStatus::Solved(self.steps.clone());
         }
+        if self.steps.len() >= self.limit {
+            self.cut_off = true;
+            let Some(came_from) = came_from else {
+                // the root still gets a frame, with nothing on it, so the pass has one to finish on
+                self.stack.push(DepthFrame {
+                    came_from: None,
+                    children: Vec::new(),
+                    next: 0,
+                });
+                return SearchStatus::Running;
+            };
+            self.board.swap(came_from);
+            self.steps.pop();
+            return SearchStatus::Running;
+        }
+
+        stats.expanded += 1;
+        stats.record_frontier(
+            self.steps.len(),
+            std::mem::size_of::<NBoard>() + self.board.search_node_bytes(self.steps.len()),
+        );
+        let pruner = self
+            .pruner
+            .get_or_insert_with(|| MovePruner::get(self.pruner_length));
+        let children = self.board.pruned_successors(pruner, state);
+        self.stack.push(DepthFrame {
+            came_from,
+            children,
+            next: 0,
+        });
+        SearchStatus::Running
     }
 }
 
+impl Expand for DepthFirstSearch {
+    fn expand(&mut self, stats: &mut SolverStats) -> SearchStatus {
+        if self.exhausted {
+            return SearchStatus::Failed;
+        }
+        let Some(frame) = self.stack.last_mut() else {
+            return self.visit(None, MovePruner::START, stats);
+        };
+
+        if let Some((next_index, state)) = frame.children.get(frame.next).copied() {
+            frame.next += 1;
+            let came_from = self.board.get_missing_index();
+            self.board.swap(next_index);
+            self.steps.push(next_index);
+            stats.generated += 1;
+            return self.visit(Some(came_from), state, stats);
+        }
+
+        if let Some(came_from) = self.stack.pop().and_then(|frame| frame.came_from) {
+            self.board.swap(came_from);
+            self.steps.pop();
+            return SearchStatus::Running;
+        }
+
+        if !self.cut_off || self.limit >= self.max_limit {
+            self.exhausted = true;
+            return SearchStatus::Failed;
+        }
+        debug!("iddfs: deepening to {}", self.limit + 1);
+        self.limit += 1;
+        self.cut_off = false;
+        SearchStatus::Running
+    }
+}
+
+fn weighted_cost(g: usize, h: usize, weight: f64) -> usize {
+    ((g as f64 + (weight * h as f64)) * COST_SCALE).round() as usize
+}
+
 fn is_even(x: usize) -> bool {
     (x as i32).rem_euclid(2) == 0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::move_pruning::DUPLICATE_PATH_LENGTH;
+    use std::collections::VecDeque;
+    use std::hash::Hash;
+
+    fn seeded(m: usize, n: usize, metric: GenerationMetric, seed: u64) -> NBoard {
+        let mut board = NBoard::new(m, n);
+        board.generate_seeded(metric, seed);
+        board
+    }
+
+    fn solves(board: &NBoard, steps: &[usize]) -> bool {
+        let mut board = board.clone();
+        for step in steps {
+            if !board.get_swappable().contains(step) {
+                return false;
+            }
+            board.swap(*step);
+        }
+        board.check_win()
+    }
+
+    // Breadth-first walk over every state reachable from `board`, with the visited set keyed by
+    // `key`.
+    fn explore_state_space<K: Eq + Hash>(board: &NBoard, key: impl Fn(&NBoard) -> K) -> HashSet<K> {
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(key(board));
+        queue.push_back(board.clone());
+
+        while let Some(board) = queue.pop_front() {
+            for swappable_index in board.get_swappable() {
+                let mut new_board = board.clone();
+                new_board.swap(swappable_index);
+                if visited.insert(key(&new_board)) {
+                    queue.push_back(new_board);
+                }
+            }
+        }
+        visited
+    }
+
+    // The board with `tiles[cell]` in each cell and the last tile as the blank.
+    fn board_of(m: usize, n: usize, goal: &Goal, tiles: &[usize]) -> NBoard {
+        let mut board = NBoard::new(m, n);
+        board.set_goal(goal).unwrap();
+        for (cell, tile) in tiles.iter().enumerate() {
+            let tile_type = match *tile == m * n - 1 {
+                true => TileType::Missing,
+                false => TileType::InPlay,
+            };
+            board.board[cell] = Tile::new(*tile, tile_type);
+        }
+        board.missing_index = tiles.iter().position(|tile| *tile == m * n - 1).unwrap();
+        board
+    }
+
+    // Steps to the next arrangement in lexicographic order, or returns false after the last one.
+    fn next_permutation(tiles: &mut [usize]) -> bool {
+        let Some(i) = (1..tiles.len()).rev().find(|i| tiles[i - 1] < tiles[*i]) else {
+            return false;
+        };
+        let j = (i..tiles.len())
+            .rev()
+            .find(|j| tiles[*j] > tiles[i - 1])
+            .unwrap();
+        tiles.swap(i - 1, j);
+        tiles[i..].reverse();
+        true
+    }
+
+    #[test]
+    fn solvable_matches_reachability() {
+        for (m, n) in [(2, 2), (3, 3), (2, 3), (3, 2)] {
+            for goal in [Goal::RowMajor, Goal::BlankFirst, Goal::Snail] {
+                let cells = goal.cells(m, n).unwrap();
+                let mut solved = vec![0; m * n];
+                for (tile, cell) in cells.iter().enumerate() {
+                    solved[*cell] = tile;
+                }
+                let reachable =
+                    explore_state_space(&board_of(m, n, &goal, &solved), NBoard::state_key);
+
+                let mut tiles: Vec<usize> = (0..m * n).collect();
+                loop {
+                    let board = board_of(m, n, &goal, &tiles);
+                    assert_eq!(
+                        board.solvable(),
+                        reachable.contains(&board.state_key()),
+                        "{}x{} {:?} goal:\n{}",
+                        m,
+                        n,
+                        goal,
+                        board
+                    );
+                    if !next_permutation(&mut tiles) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    // Compares the old Debug-string encoding against the packed StateKey over every reachable 3x3
+    // state. Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn benchmark_state_keys() {
+        let board = seeded(3, 3, GenerationMetric::Random(100), 0);
+
+        let start = std::time::Instant::now();
+        let states = explore_state_space(&board, NBoard::to_string_representation).len();
+        let string_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        assert_eq!(explore_state_space(&board, NBoard::state_key).len(), states);
+        let packed_time = start.elapsed();
+
+        println!(
+            "3x3 full exploration ({} states): string {:?}, packed {:?}",
+            states, string_time, packed_time
+        );
+        assert_eq!(states, 181440);
+    }
+
+    // Shared seeds have to keep meaning the same board across releases and platforms.
+    #[test]
+    fn seed_always_gives_the_same_board() {
+        let board = seeded(3, 3, GenerationMetric::Random(100), 42);
+        let tiles: Vec<usize> = (0..9).map(|cell| board.index_at(cell)).collect();
+        assert_eq!(tiles, [1, 5, 2, 4, 6, 8, 3, 0, 7]);
+        assert_eq!(board.get_missing_index(), 4);
+
+        let board = seeded(3, 3, GenerationMetric::UniformRandom, 42);
+        let tiles: Vec<usize> = (0..9).map(|cell| board.index_at(cell)).collect();
+        assert_eq!(tiles, [7, 3, 0, 5, 8, 4, 2, 6, 1]);
+        assert_eq!(board.get_missing_index(), 7);
+    }
+
+    #[test]
+    fn generate_at_depth_is_exact() {
+        for (m, n, moves) in [(3, 3, 25), (2, 4, 12), (4, 4, 30)] {
+            let mut board = NBoard::new(m, n);
+            let mut rng = NBoard::seeded_rng(3);
+            assert_eq!(board.generate_at_depth(moves, moves, &mut rng), Ok(moves));
+            let steps =
+                board.ida_star_search(NBoard::walking_distance, moves, &mut SolverStats::default());
+            assert_eq!(steps.map(|steps| steps.len()), Some(moves));
+        }
+        let mut board = NBoard::new(3, 3);
+        assert!(board
+            .generate_at_depth(40, 40, &mut NBoard::seeded_rng(3))
+            .is_err());
+    }
+
+    #[test]
+    fn generated_blank_follows_the_goal() {
+        let metrics = [
+            GenerationMetric::Random(100),
+            GenerationMetric::MaxManhattanDistance,
+            GenerationMetric::OptimalDepth(10, 12),
+            GenerationMetric::UniformRandom,
+        ];
+        for (m, n) in [(3, 3), (4, 4)] {
+            for goal in [Goal::BlankFirst, Goal::Snail] {
+                for metric in metrics {
+                    for seed in 0..5 {
+                        let mut board = NBoard::new(m, n);
+                        board.set_goal(&goal).unwrap();
+                        board.generate_seeded(metric, seed);
+                        let blank = board.index_at(board.get_missing_index());
+                        assert_eq!(blank, m * n - 1, "{:?} {:?} seed {}", goal, metric, seed);
+                        assert!(board.solvable());
+                    }
+                }
+            }
+        }
+    }
+
+    // Difficulty picks these metrics for Easy through Expert on boards without a distance table.
+    #[test]
+    fn metrics_scramble_3x3_progressively_deeper() {
+        let metrics = [
+            GenerationMetric::Random(100),
+            GenerationMetric::MaxTilesOut,
+            GenerationMetric::MaxManhattanDistance,
+            GenerationMetric::MaxEuclideanDistance,
+        ];
+        let averages: Vec<f64> = metrics
+            .iter()
+            .map(|metric| {
+                let total: usize = (0..300)
+                    .map(|seed| seeded(3, 3, *metric, seed).optimal_distance().unwrap())
+                    .sum();
+                total as f64 / 300.
+            })
+            .collect();
+        assert!(
+            averages.windows(2).all(|pair| pair[0] < pair[1]),
+            "{:?}",
+            averages
+        );
+    }
+
+    #[test]
+    fn ara_star_improves_to_optimal() {
+        for seed in 0..10 {
+            let board = seeded(3, 3, GenerationMetric::UniformRandom, seed);
+            let solutions = board.ara_star_solve_with(NBoard::manhattan_distance, 3., 10_000);
+            let last = solutions.last().expect("a solution within the budget");
+            assert!(solutions
+                .iter()
+                .all(|solution| solves(&board, &solution.steps)));
+            assert!(solutions
+                .windows(2)
+                .all(|pair| pair[1].steps.len() <= pair[0].steps.len()));
+            assert_eq!(last.bound, 1.);
+            assert_eq!(
+                Some(last.steps.len()),
+                board.optimal_distance(),
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn ara_star_budget_waits_for_the_first_solution() {
+        let board = seeded(4, 4, GenerationMetric::Random(60), 1);
+        let solutions = board.ara_star_solve_with(NBoard::manhattan_distance, 3., 0);
+        assert_eq!(solutions.len(), 1);
+        assert!(solves(&board, &solutions[0].steps));
+        assert!(solutions[0].bound <= 3.);
+    }
+
+    #[test]
+    fn ida_star_is_optimal_on_3x3() {
+        for seed in 0..20 {
+            let board = seeded(3, 3, GenerationMetric::UniformRandom, seed);
+            let steps = board
+                .ida_star_solve()
+                .expect("every generated board is solvable");
+            assert!(solves(&board, &steps), "seed {}", seed);
+            assert_eq!(Some(steps.len()), board.optimal_distance(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn bidirectional_is_optimal_on_3x3() {
+        for seed in 0..20 {
+            let board = seeded(3, 3, GenerationMetric::UniformRandom, seed);
+            for steps in [
+                board.bidirectional_solve(),
+                board.bidirectional_solve_with(|_| 0),
+            ] {
+                let steps = steps.expect("every generated board is solvable");
+                assert!(solves(&board, &steps), "seed {}", seed);
+                assert_eq!(Some(steps.len()), board.optimal_distance(), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn depth_limited_search_respects_its_limit() {
+        let mut board = NBoard::new(3, 3);
+        let mut one_move = NBoard::new(3, 3);
+        one_move.generate_seeded(GenerationMetric::OptimalDepth(1, 1), 0);
+        assert_eq!(one_move.dfs_solve(0), None);
+        assert_eq!(one_move.dfs_solve(1).map(|steps| steps.len()), Some(1));
+        assert_eq!(one_move.iddfs_solve(0), None);
+
+        for seed in 0..10 {
+            board.generate_seeded(GenerationMetric::OptimalDepth(6, 12), seed);
+            let optimal = board.optimal_distance().unwrap();
+            let steps = board.dfs_solve(optimal + 4).expect("the limit leaves room");
+            assert!(steps.len() <= optimal + 4, "seed {}", seed);
+            assert!(solves(&board, &steps), "seed {}", seed);
+            // one short of the optimal solution, nothing can be found
+            assert_eq!(board.dfs_solve(optimal - 1), None, "seed {}", seed);
+            assert_eq!(board.iddfs_solve(optimal - 1), None, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn iterative_deepening_matches_a_star_on_3x3() {
+        for seed in 0..10 {
+            let board = seeded(3, 3, GenerationMetric::OptimalDepth(8, 14), seed);
+            let expected = board
+                .weighted_a_star_search(NBoard::manhattan_distance, 1., &mut SolverStats::default())
+                .expect("every generated board is solvable");
+            let steps = board
+                .iddfs_solve(expected.len())
+                .expect("found within A*'s depth");
+            assert_eq!(steps.len(), expected.len(), "seed {}", seed);
+            assert!(solves(&board, &steps), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn duplicate_pruning_keeps_ida_star_optimal() {
+        for seed in 0..20 {
+            let board = seeded(3, 3, GenerationMetric::UniformRandom, seed);
+            let mut search = IdaStarSearch::new(&board, NBoard::manhattan_distance, usize::MAX)
+                .with_pruner(DUPLICATE_PATH_LENGTH);
+            let steps = run_to_completion(&mut search, &mut SolverStats::default())
+                .expect("every generated board is solvable");
+            assert!(solves(&board, &steps), "seed {}", seed);
+            assert_eq!(Some(steps.len()), board.optimal_distance(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn ida_star_matches_a_star_on_4x4() {
+        for seed in 0..5 {
+            let board = seeded(4, 4, GenerationMetric::Random(30), seed);
+            let steps = board
+                .ida_star_solve_with(NBoard::linear_conflict)
+                .expect("every generated board is solvable");
+            assert!(solves(&board, &steps), "seed {}", seed);
+            assert_eq!(
+                Some(steps.len()),
+                board.a_star_solve().map(|steps| steps.len()),
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn ida_star_stops_at_max_bound() {
+        let board = seeded(3, 3, GenerationMetric::OptimalDepth(20, 20), 7);
+        let mut stats = SolverStats::default();
+        assert_eq!(
+            board.ida_star_search(NBoard::manhattan_distance, 19, &mut stats),
+            None
+        );
+        assert!(stats.expanded > 0);
+        assert!(board
+            .ida_star_search(NBoard::manhattan_distance, 20, &mut stats)
+            .is_some());
+    }
+}
@@ -0,0 +1,388 @@
+/**
+ * @file parallel_a_star.rs
+ *
+ * @brief This is the module that spreads A* over every core of a native build, using hash
+ * distributed A* (HDA*) on std threads. It is only compiled with the "parallel" feature, and never
+ * for wasm32, where there are no threads to spread over.
+ *
+ * Every state belongs to exactly one thread, picked by hashing its key. A thread only expands states
+ * it owns, and hands each successor to its owner over a channel, so no open or closed list is ever
+ * shared. Threads discard anything that can't beat the shortest solution found so far, and a count
+ * of the nodes still open or in flight tells them when there is nothing left that could. With an
+ * admissible heuristic the solution is then optimal, the same length A* finds.
+ *
+ * ParallelSearch runs the threads in the background behind Expand, so a SolverHandle can drive and
+ * cancel HDA* like any other search. Cancelling sets a flag every thread checks before its next node.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use crate::npuzzle::{NBoard, StateKey};
+use crate::search::{Expand, SearchStatus};
+use crate::solver_stats::SolverStats;
+use log::debug;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{self, AtomicBool, AtomicUsize};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// How long an idle thread waits for work before checking whether the search is over.
+const IDLE_WAIT_MS: u64 = 1;
+
+struct Node {
+    f: usize,
+    board: NBoard,
+    steps: Vec<usize>,
+    // where the blank was before the move that led here; None at the root
+    came_from: Option<usize>,
+}
+
+// Lowest f first, and the deepest of those.
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .f
+            .cmp(&self.f)
+            .then(self.steps.len().cmp(&other.steps.len()))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        (self.f, self.steps.len()) == (other.f, other.steps.len())
+    }
+}
+
+impl Eq for Node {}
+
+// What the threads share: the shortest solution so far, how many nodes are open or in flight, and
+// whether the search was called off.
+struct Shared {
+    best: Mutex<Option<Vec<usize>>>,
+    best_length: AtomicUsize,
+    work: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl Default for Shared {
+    fn default() -> Self {
+        Self {
+            best: Mutex::new(None),
+            best_length: AtomicUsize::new(usize::MAX),
+            work: AtomicUsize::new(1),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+}
+
+// The solution, if any, and each thread's stats.
+type Outcome = (Option<Vec<usize>>, Vec<SolverStats>);
+
+// HDA* a SolverHandle can drive. The first expand starts the threads, and each one after waits a
+// moment for them to finish. Dropping the search, as cancelling its handle does, stops the threads.
+pub struct ParallelSearch<H> {
+    board: NBoard,
+    // handed to the threads when they start
+    heuristic: Option<H>,
+    threads: usize,
+    shared: Arc<Shared>,
+    outcome: Option<Receiver<Outcome>>,
+}
+
+impl<H: Fn(&NBoard) -> usize + Send + Sync + 'static> ParallelSearch<H> {
+    pub fn new(board: &NBoard, heuristic: H, threads: usize) -> Self {
+        Self {
+            board: board.clone(),
+            heuristic: Some(heuristic),
+            threads,
+            shared: Arc::default(),
+            outcome: None,
+        }
+    }
+}
+
+impl<H: Fn(&NBoard) -> usize + Send + Sync + 'static> Expand for ParallelSearch<H> {
+    fn expand(&mut self, stats: &mut SolverStats) -> SearchStatus {
+        if let Some(heuristic) = self.heuristic.take() {
+            let (sender, receiver) = mpsc::channel();
+            let (board, threads, shared) = (self.board.clone(), self.threads, self.shared.clone());
+            std::thread::spawn(move || {
+                sender
+                    .send(search(&board, &heuristic, threads, &shared))
+                    .ok();
+            });
+            self.outcome = Some(receiver);
+        }
+        let Some(outcome) = &self.outcome else {
+            return SearchStatus::Failed;
+        };
+        match outcome.recv_timeout(Duration::from_millis(IDLE_WAIT_MS)) {
+            Ok((steps, thread_stats)) => {
+                self.outcome = None;
+                record(stats, thread_stats);
+                match steps {
+                    Some(steps) => SearchStatus::Solved(steps),
+                    None => SearchStatus::Failed,
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => SearchStatus::Running,
+            Err(RecvTimeoutError::Disconnected) => SearchStatus::Failed,
+        }
+    }
+}
+
+impl<H> Drop for ParallelSearch<H> {
+    fn drop(&mut self) {
+        self.shared.cancelled.store(true, atomic::Ordering::SeqCst);
+    }
+}
+
+// Solves `board` on `threads` threads.
+pub fn solve<H: Fn(&NBoard) -> usize + Sync>(
+    board: &NBoard,
+    heuristic: &H,
+    threads: usize,
+) -> Outcome {
+    search(board, heuristic, threads, &Shared::default())
+}
+
+fn search<H: Fn(&NBoard) -> usize + Sync>(
+    board: &NBoard,
+    heuristic: &H,
+    threads: usize,
+    shared: &Shared,
+) -> Outcome {
+    let threads = threads.max(1);
+    if !board.solvable() {
+        return (None, vec![SolverStats::default(); threads]);
+    }
+
+    let (senders, receivers): (Vec<Sender<Node>>, Vec<Receiver<Node>>) =
+        (0..threads).map(|_| mpsc::channel()).unzip();
+    senders[owner(&board.state_key(), threads)]
+        .send(Node {
+            f: heuristic(board),
+            board: board.clone(),
+            steps: Vec::default(),
+            came_from: None,
+        })
+        .unwrap();
+
+    let stats: Vec<SolverStats> = std::thread::scope(|scope| {
+        let workers: Vec<_> = receivers
+            .into_iter()
+            .map(|inbox| {
+                let senders = senders.clone();
+                scope.spawn(move || run(inbox, &senders, heuristic, shared))
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    for (thread, stats) in stats.iter().enumerate() {
+        debug!("hda*: thread {}: {}", thread, stats);
+    }
+    let best = shared.best.lock().unwrap().take();
+    (best, stats)
+}
+
+// Adds the threads' work to `stats`, and keeps each thread's own for the stats panel.
+pub fn record(stats: &mut SolverStats, threads: Vec<SolverStats>) {
+    let total = total(&threads);
+    stats.expanded += total.expanded;
+    stats.generated += total.generated;
+    stats.record_frontier(total.max_frontier, total.peak_memory_bytes);
+    stats.threads = threads;
+}
+
+// The threads' work added up. Their frontiers and memory were held at the same time, so their peaks
+// are summed too, which can only overstate the real peak.
+fn total(threads: &[SolverStats]) -> SolverStats {
+    let mut total = SolverStats::default();
+    for stats in threads {
+        total.expanded += stats.expanded;
+        total.generated += stats.generated;
+        total.max_frontier += stats.max_frontier;
+        total.peak_memory_bytes += stats.peak_memory_bytes;
+    }
+    total
+}
+
+fn run<H: Fn(&NBoard) -> usize>(
+    inbox: Receiver<Node>,
+    senders: &[Sender<Node>],
+    heuristic: &H,
+    shared: &Shared,
+) -> SolverStats {
+    let mut stats = SolverStats::default();
+    let mut open: BinaryHeap<Node> = BinaryHeap::new();
+    // the fewest moves each owned state has been reached in
+    let mut reached: HashMap<StateKey, usize> = HashMap::new();
+
+    loop {
+        if shared.cancelled.load(atomic::Ordering::SeqCst) {
+            return stats;
+        }
+        for node in inbox.try_iter() {
+            open.push(node);
+        }
+        let Some(node) = open.pop() else {
+            if shared.work.load(atomic::Ordering::SeqCst) == 0 {
+                return stats;
+            }
+            match inbox.recv_timeout(Duration::from_millis(IDLE_WAIT_MS)) {
+                Ok(node) => open.push(node),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return stats,
+            }
+            continue;
+        };
+
+        let node_bytes =
+            std::mem::size_of::<Node>() + node.board.search_node_bytes(node.steps.len());
+        let key_bytes = node.board.state_key_bytes() + std::mem::size_of::<usize>();
+        expand(node, senders, heuristic, shared, &mut reached, &mut stats);
+        stats.record_frontier(
+            open.len(),
+            (open.len() * node_bytes) + (reached.len() * key_bytes),
+        );
+        // the node's successors were counted before it stops counting, so work only reaches zero
+        // once every thread is out of nodes
+        shared.work.fetch_sub(1, atomic::Ordering::SeqCst);
+    }
+}
+
+fn expand<H: Fn(&NBoard) -> usize>(
+    node: Node,
+    senders: &[Sender<Node>],
+    heuristic: &H,
+    shared: &Shared,
+    reached: &mut HashMap<StateKey, usize>,
+    stats: &mut SolverStats,
+) {
+    if node.f >= shared.best_length.load(atomic::Ordering::SeqCst) {
+        return;
+    }
+    let key = node.board.state_key();
+    if matches!(reached.get(&key), Some(g) if *g <= node.steps.len()) {
+        return;
+    }
+    reached.insert(key, node.steps.len());
+
+    if node.board.check_win() {
+        let mut best = shared.best.lock().unwrap();
+        if node.steps.len() < shared.best_length.load(atomic::Ordering::SeqCst) {
+            shared
+                .best_length
+                .store(node.steps.len(), atomic::Ordering::SeqCst);
+            *best = Some(node.steps);
+        }
+        return;
+    }
+
+    stats.expanded += 1;
+    let blank = node.board.get_missing_index();
+    for swappable_index in node.board.successors(node.came_from) {
+        let mut new_board = node.board.clone();
+        new_board.swap(swappable_index);
+        stats.generated += 1;
+
+        let g = node.steps.len() + 1;
+        let f = g + heuristic(&new_board);
+        if f >= shared.best_length.load(atomic::Ordering::SeqCst) {
+            continue;
+        }
+        let mut steps = node.steps.clone();
+        steps.push(swappable_index);
+        let owner = owner(&new_board.state_key(), senders.len());
+        shared.work.fetch_add(1, atomic::Ordering::SeqCst);
+        let child = Node {
+            f,
+            board: new_board,
+            steps,
+            came_from: Some(blank),
+        };
+        if senders[owner].send(child).is_err() {
+            shared.work.fetch_sub(1, atomic::Ordering::SeqCst);
+        }
+    }
+}
+
+fn owner(key: &StateKey, threads: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % threads as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::npuzzle::{AStarSearch, GenerationMetric};
+    use crate::search::run_to_completion;
+
+    fn seeded(seed: u64) -> NBoard {
+        let mut board = NBoard::new(3, 3);
+        board.generate_seeded(GenerationMetric::UniformRandom, seed);
+        board
+    }
+
+    #[test]
+    fn matches_a_star_on_3x3() {
+        for seed in 0..20 {
+            let board = seeded(seed);
+            let mut search = AStarSearch::new(&board, NBoard::manhattan_distance, 1.);
+            let expected = run_to_completion(&mut search, &mut SolverStats::default())
+                .expect("every generated board is solvable");
+            for threads in [1, 4] {
+                let (steps, stats) = solve(&board, &NBoard::manhattan_distance, threads);
+                let steps = steps.expect("every generated board is solvable");
+                assert_eq!(steps.len(), expected.len(), "seed {}", seed);
+                assert_eq!(stats.len(), threads);
+            }
+        }
+    }
+
+    #[test]
+    fn cancelling_stops_the_threads() {
+        let mut parallel = ParallelSearch::new(&seeded(0), NBoard::manhattan_distance, 4);
+        parallel.expand(&mut SolverStats::default());
+        let shared = parallel.shared.clone();
+        drop(parallel);
+        assert!(shared.cancelled.load(atomic::Ordering::SeqCst));
+
+        let shared = Shared::default();
+        shared.cancelled.store(true, atomic::Ordering::SeqCst);
+        let (steps, _) = search(&seeded(0), &NBoard::manhattan_distance, 4, &shared);
+        assert_eq!(steps, None);
+    }
+
+    #[test]
+    fn search_records_each_thread() {
+        let board = seeded(1);
+        let mut search = ParallelSearch::new(&board, NBoard::manhattan_distance, 3);
+        let mut stats = SolverStats::default();
+        let status = loop {
+            match search.expand(&mut stats) {
+                SearchStatus::Running => continue,
+                status => break status,
+            }
+        };
+        assert!(matches!(status, SearchStatus::Solved(_)));
+        assert_eq!(stats.threads.len(), 3);
+        let expanded: usize = stats.threads.iter().map(|thread| thread.expanded).sum();
+        assert_eq!(stats.expanded, expanded);
+    }
+}
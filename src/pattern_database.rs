@@ -0,0 +1,771 @@
+/**
+ * @file pattern_database.rs
+ *
+ * @brief This is the module that builds, saves and loads disjoint additive pattern databases, which
+ * NBoard's A* and IDA* can use as a heuristic in place of manhattan_distance.
+ *
+ * Every pattern is a group of tiles. Its table stores, for every placement of those tiles, the fewest
+ * moves *of pattern tiles* needed to bring them home, with every other tile treated as
+ * interchangeable. Because no two patterns share a tile and the blank belongs to none of them, the
+ * per-pattern distances can be summed without overestimating.
+ *
+ * Patterns are built against the row major goal. Boards with another goal are read by the goal cell
+ * of each tile instead, so one database serves every goal.
+ *
+ * Square boards are looked up a second time mirrored in their main diagonal, which for the usual
+ * goal puts the tiles of one pattern where another's were, and the larger of the two sums is used.
+ *
+ * The 6-6-3 split takes several seconds to build, so native builds keep every database they build
+ * in the temp directory and load it from there the next time. The web only ever builds a database
+ * a DatabaseBuilder step at a time, before the search that needs it starts.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use crate::npuzzle::NBoard;
+use crate::search::Build;
+use log::{debug, error};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// The usual split for the 15 puzzle, and a cheaper one to compare it against.
+pub const PARTITION_6_6_3: [usize; 3] = [6, 6, 3];
+pub const PARTITION_5_5_5: [usize; 3] = [5, 5, 5];
+// 3x3 boards only have 8 tiles to share out.
+pub const PARTITION_4_4: [usize; 2] = [4, 4];
+
+const MAGIC: &[u8; 4] = b"NPDB";
+const VERSION: u8 = 1;
+// A 7 tile pattern on a 4x4 board already needs ~100MB of scratch space while building.
+const MAX_PATTERN_SIZE: usize = 7;
+// Cell sets are kept in a u64 bitmask.
+const MAX_CELLS: usize = 64;
+const UNSEEN: u8 = u8::MAX;
+// Frontier states a DatabaseBuilder expands per step.
+const BUILD_STATES: usize = 64;
+
+type DatabaseKey = (usize, usize, usize, &'static [usize]);
+
+static DATABASES: Mutex<Option<HashMap<DatabaseKey, Arc<PatternDatabase>>>> = Mutex::new(None);
+
+pub struct PatternDatabase {
+    rows: usize,
+    cols: usize,
+    patterns: Vec<Pattern>,
+}
+
+struct Pattern {
+    tiles: Vec<usize>,
+    goals: Vec<usize>,
+    distances: Vec<u8>,
+}
+
+impl PatternDatabase {
+    // One database per board shape, blank and partition, loaded or built the first time it's asked
+    // for.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn get(
+        rows: usize,
+        cols: usize,
+        blank_tile: usize,
+        sizes: &'static [usize],
+    ) -> Result<Arc<Self>, String> {
+        let mut databases = DATABASES.lock().unwrap();
+        let databases = databases.get_or_insert_with(HashMap::new);
+        let key = (rows, cols, blank_tile, sizes);
+        if let Some(database) = databases.get(&key) {
+            return Ok(database.clone());
+        }
+        let database = Arc::new(Self::load_or_build(rows, cols, blank_tile, sizes)?);
+        databases.insert(key, database.clone());
+        Ok(database)
+    }
+
+    // The database, if it can be had without holding up the web's frame: natively it is loaded or
+    // built on the spot, on the web only a database that is already built will do.
+    pub fn ready(
+        rows: usize,
+        cols: usize,
+        blank_tile: usize,
+        sizes: &'static [usize],
+    ) -> Option<Arc<Self>> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Self::get(rows, cols, blank_tile, sizes).ok()
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            Self::cached(rows, cols, blank_tile, sizes)
+        }
+    }
+
+    fn cached(
+        rows: usize,
+        cols: usize,
+        blank_tile: usize,
+        sizes: &'static [usize],
+    ) -> Option<Arc<Self>> {
+        DATABASES
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|databases| databases.get(&(rows, cols, blank_tile, sizes)).cloned())
+    }
+
+    // None once the database is built (or, natively, saved by an earlier run), and for partitions
+    // that can't make one.
+    pub fn builder(
+        rows: usize,
+        cols: usize,
+        blank_tile: usize,
+        sizes: &'static [usize],
+    ) -> Option<DatabaseBuilder> {
+        if Self::cached(rows, cols, blank_tile, sizes).is_some() {
+            return None;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(database) = Self::load(&Self::cache_path(rows, cols, blank_tile, sizes)) {
+            if database.rows == rows && database.cols == cols {
+                Self::install((rows, cols, blank_tile, sizes), database);
+                return None;
+            }
+        }
+
+        let groups = Self::partition(rows, cols, blank_tile, sizes);
+        Self::validate(rows, cols, &groups).ok()?;
+        Some(DatabaseBuilder {
+            key: (rows, cols, blank_tile, sizes),
+            groups,
+            patterns: Vec::default(),
+            pattern: None,
+        })
+    }
+
+    fn install(key: DatabaseKey, database: Self) {
+        DATABASES
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .insert(key, Arc::new(database));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn cache_path(
+        rows: usize,
+        cols: usize,
+        blank_tile: usize,
+        sizes: &[usize],
+    ) -> std::path::PathBuf {
+        let sizes_name: Vec<String> = sizes.iter().map(|size| size.to_string()).collect();
+        std::env::temp_dir().join("npuzzle").join(format!(
+            "{}x{}_{}_{}.npdb",
+            rows,
+            cols,
+            blank_tile,
+            sizes_name.join("-")
+        ))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_or_build(
+        rows: usize,
+        cols: usize,
+        blank_tile: usize,
+        sizes: &[usize],
+    ) -> Result<Self, String> {
+        let path = Self::cache_path(rows, cols, blank_tile, sizes);
+        if let Ok(database) = Self::load(&path) {
+            if database.rows == rows && database.cols == cols {
+                return Ok(database);
+            }
+        }
+
+        let database = Self::build(rows, cols, &Self::partition(rows, cols, blank_tile, sizes))?;
+        database.save_cached(&path);
+        Ok(database)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_cached(&self, path: &std::path::Path) {
+        let saved = std::fs::create_dir_all(path.parent().unwrap()).and_then(|_| self.save(path));
+        if let Err(e) = saved {
+            error!("could not save {}: {}", path.display(), e);
+        }
+    }
+
+    // Splits every tile but the blank into groups of the given sizes. Tight, connected groups make
+    // much stronger patterns than runs of consecutive tiles, so each group is seeded from the most
+    // cornered free cell and grown through whichever neighbour has the fewest free neighbours left.
+    pub fn partition(
+        rows: usize,
+        cols: usize,
+        blank_tile: usize,
+        sizes: &[usize],
+    ) -> Vec<Vec<usize>> {
+        let cells = rows * cols;
+        let mut free: Vec<bool> = (0..cells).map(|tile| tile != blank_tile).collect();
+        let free_neighbours = |free: &[bool], cell: usize| {
+            neighbours(cell, rows, cols)
+                .into_iter()
+                .flatten()
+                .filter(|neighbour| free[*neighbour])
+                .count()
+        };
+
+        let mut groups = Vec::default();
+        for size in sizes {
+            let mut group: Vec<usize> = Vec::default();
+            while group.len() < *size {
+                let candidates: Vec<usize> = if group.is_empty() {
+                    (0..cells).filter(|cell| free[*cell]).collect()
+                } else {
+                    (0..cells)
+                        .filter(|cell| free[*cell])
+                        .filter(|cell| {
+                            neighbours(*cell, rows, cols)
+                                .into_iter()
+                                .flatten()
+                                .any(|neighbour| group.contains(&neighbour))
+                        })
+                        .collect()
+                };
+                match candidates
+                    .into_iter()
+                    .min_by_key(|cell| free_neighbours(&free, *cell))
+                {
+                    Some(cell) => {
+                        free[cell] = false;
+                        group.push(cell);
+                    }
+                    None => break,
+                }
+            }
+            if !group.is_empty() {
+                group.sort();
+                groups.push(group);
+            }
+        }
+        groups
+    }
+
+    pub fn build(rows: usize, cols: usize, groups: &[Vec<usize>]) -> Result<Self, String> {
+        Self::validate(rows, cols, groups)?;
+        let patterns = groups
+            .iter()
+            .map(|group| {
+                let mut builder = PatternBuilder::new(rows, cols, group.clone(), group.clone());
+                while !builder.step(usize::MAX) {}
+                builder.finish()
+            })
+            .collect();
+
+        Ok(Self {
+            rows,
+            cols,
+            patterns,
+        })
+    }
+
+    fn validate(rows: usize, cols: usize, groups: &[Vec<usize>]) -> Result<(), String> {
+        let cells = rows * cols;
+        if cells > MAX_CELLS {
+            return Err(format!(
+                "a {}x{} board has more than {} cells",
+                rows, cols, MAX_CELLS
+            ));
+        }
+
+        let mut seen = vec![false; cells];
+        for group in groups {
+            if group.is_empty() || group.len() > MAX_PATTERN_SIZE {
+                return Err(format!(
+                    "patterns must hold between 1 and {} tiles, got {}",
+                    MAX_PATTERN_SIZE,
+                    group.len()
+                ));
+            }
+            for tile in group {
+                if *tile >= cells {
+                    return Err(format!(
+                        "tile {} does not exist on a {}x{} board",
+                        tile, rows, cols
+                    ));
+                }
+                if seen[*tile] {
+                    return Err(format!("tile {} appears in more than one pattern", tile));
+                }
+                seen[*tile] = true;
+            }
+        }
+        if seen.iter().all(|s| *s) {
+            return Err("the patterns must leave at least one tile out for the blank".to_owned());
+        }
+        Ok(())
+    }
+
+    // Boards of another size, or whose blank is one of the pattern tiles, would not get an
+    // admissible estimate out of this database.
+    pub fn is_compatible(&self, board: &NBoard) -> bool {
+        if board.get_m() != self.rows
+            || board.get_n() != self.cols
+            || board.get_missing_index() >= self.rows * self.cols
+        {
+            return false;
+        }
+        let blank_tile = board.goal_cell(board.index_at(board.get_missing_index()));
+        !self.patterns.iter().any(|p| p.tiles.contains(&blank_tile))
+    }
+
+    pub fn distance(&self, board: &NBoard) -> usize {
+        if !self.is_compatible(board) {
+            return board.manhattan_distance();
+        }
+
+        let cells = self.rows * self.cols;
+        let mut cell_of = vec![0; cells];
+        for cell in 0..cells {
+            cell_of[board.goal_cell(board.index_at(cell))] = cell;
+        }
+        let direct = self.lookup(&cell_of);
+
+        // mirrored in its main diagonal, a square board is exactly as far from its mirrored goal,
+        // so the same tables give a second estimate whenever the mirrored blank suits them too
+        let transpose = |cell: usize| (cell % self.cols) * self.cols + (cell / self.cols);
+        let blank_goal = board.goal_cell(board.index_at(board.get_missing_index()));
+        if self.rows != self.cols
+            || self
+                .patterns
+                .iter()
+                .any(|p| p.tiles.contains(&transpose(blank_goal)))
+        {
+            return direct;
+        }
+        let transposed: Vec<usize> = (0..cells)
+            .map(|tile| transpose(cell_of[transpose(tile)]))
+            .collect();
+        direct.max(self.lookup(&transposed))
+    }
+
+    // `cell_of[tile]` is where the tile whose goal cell is `tile` sits.
+    fn lookup(&self, cell_of: &[usize]) -> usize {
+        let cells = self.rows * self.cols;
+        self.patterns
+            .iter()
+            .map(|pattern| {
+                let placement: Vec<usize> = pattern.tiles.iter().map(|t| cell_of[*t]).collect();
+                pattern.distances[rank(&placement, cells)] as usize
+            })
+            .sum()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::default();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.rows as u8);
+        bytes.push(self.cols as u8);
+        bytes.push(self.patterns.len() as u8);
+        for pattern in &self.patterns {
+            bytes.push(pattern.tiles.len() as u8);
+            for (tile, goal) in pattern.tiles.iter().zip(&pattern.goals) {
+                bytes.push(*tile as u8);
+                bytes.push(*goal as u8);
+            }
+            bytes.extend_from_slice(&(pattern.distances.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&pattern.distances);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err("not a pattern database file".to_owned());
+        }
+        let version = reader.byte()?;
+        if version != VERSION {
+            return Err(format!("unsupported pattern database version {}", version));
+        }
+
+        let rows = reader.byte()? as usize;
+        let cols = reader.byte()? as usize;
+        let cells = rows * cols;
+        let pattern_count = reader.byte()? as usize;
+
+        let mut patterns = Vec::default();
+        for _ in 0..pattern_count {
+            let size = reader.byte()? as usize;
+            if size == 0 || size > MAX_PATTERN_SIZE || size >= cells {
+                return Err(format!("pattern of {} tiles is not supported", size));
+            }
+            let mut tiles = Vec::default();
+            let mut goals = Vec::default();
+            for _ in 0..size {
+                tiles.push(reader.byte()? as usize);
+                goals.push(reader.byte()? as usize);
+            }
+            if tiles.iter().chain(&goals).any(|i| *i >= cells) {
+                return Err(format!("pattern tiles out of range for {}x{}", rows, cols));
+            }
+
+            let len = u32::from_le_bytes(reader.take(4)?.try_into().unwrap()) as usize;
+            if len != permutations(cells, size) {
+                return Err(format!(
+                    "pattern of {} tiles should hold {} entries, found {}",
+                    size,
+                    permutations(cells, size),
+                    len
+                ));
+            }
+            let distances = reader.take(len)?.to_vec();
+            patterns.push(Pattern {
+                tiles,
+                goals,
+                distances,
+            });
+        }
+        if reader.offset != bytes.len() {
+            return Err("trailing bytes after the last pattern".to_owned());
+        }
+
+        Ok(Self {
+            rows,
+            cols,
+            patterns,
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+// Breadth-first search outward from the goal placement, one pattern tile move per layer. The blank
+// slides around the other tiles for free, so rather than tracking its cell we only track which
+// connected region of non-pattern cells it is in, named by that region's lowest cell.
+struct PatternBuilder {
+    rows: usize,
+    cols: usize,
+    tiles: Vec<usize>,
+    goals: Vec<usize>,
+    distances: Vec<u8>,
+    visited: Vec<u64>,
+    frontier: Vec<u64>,
+    next_frontier: Vec<u64>,
+    // how far through the frontier the search is
+    position: usize,
+    depth: u8,
+}
+
+impl PatternBuilder {
+    fn new(rows: usize, cols: usize, tiles: Vec<usize>, goals: Vec<usize>) -> Self {
+        let cells = rows * cols;
+        let size = permutations(cells, tiles.len());
+        let mut distances = vec![UNSEEN; size];
+        let mut visited = vec![0u64; ((size * cells) + 63) / 64];
+
+        let mut frontier = Vec::default();
+        let occupied = mask(&goals);
+        distances[rank(&goals, cells)] = 0;
+        for cell in 0..cells {
+            if occupied & bit(cell) != 0 {
+                continue;
+            }
+            let region = flood(cell, occupied, rows, cols);
+            if region.trailing_zeros() as usize == cell {
+                mark(&mut visited, (rank(&goals, cells) * cells) + cell);
+                frontier.push(pack(&goals, cell));
+            }
+        }
+
+        Self {
+            rows,
+            cols,
+            tiles,
+            goals,
+            distances,
+            visited,
+            frontier,
+            next_frontier: Vec::default(),
+            position: 0,
+            depth: 0,
+        }
+    }
+
+    // Expands up to `states` frontier states, returning true once every placement has been reached.
+    fn step(&mut self, states: usize) -> bool {
+        let (rows, cols) = (self.rows, self.cols);
+        let cells = rows * cols;
+        let k = self.tiles.len();
+        let mut placement = [0; MAX_PATTERN_SIZE];
+        let end = self
+            .frontier
+            .len()
+            .min(self.position.saturating_add(states));
+        for state in &self.frontier[self.position..end] {
+            let blank = unpack(*state, &mut placement[..k]);
+            let occupied = mask(&placement[..k]);
+            let region = flood(blank, occupied, rows, cols);
+            for i in 0..k {
+                let cell = placement[i];
+                for neighbour in neighbours(cell, rows, cols).into_iter().flatten() {
+                    if region & bit(neighbour) == 0 {
+                        continue;
+                    }
+                    // the tile slides into the blank's region, leaving the blank behind it
+                    placement[i] = neighbour;
+                    let moved_occupied = (occupied & !bit(cell)) | bit(neighbour);
+                    let moved_blank =
+                        flood(cell, moved_occupied, rows, cols).trailing_zeros() as usize;
+
+                    let moved_rank = rank(&placement[..k], cells);
+                    let moved_state = (moved_rank * cells) + moved_blank;
+                    if !is_marked(&self.visited, moved_state) {
+                        mark(&mut self.visited, moved_state);
+                        if self.distances[moved_rank] == UNSEEN {
+                            self.distances[moved_rank] = self.depth + 1;
+                        }
+                        self.next_frontier.push(pack(&placement[..k], moved_blank));
+                    }
+                    placement[i] = cell;
+                }
+            }
+        }
+        self.position = end;
+        if self.position < self.frontier.len() {
+            return false;
+        }
+
+        self.frontier = std::mem::take(&mut self.next_frontier);
+        self.position = 0;
+        self.depth += 1;
+        self.frontier.is_empty()
+    }
+
+    fn finish(self) -> Pattern {
+        debug!(
+            "pattern database: {:?} built with {} entries, deepest {}",
+            self.tiles,
+            self.distances.len(),
+            self.depth.saturating_sub(1)
+        );
+        Pattern {
+            tiles: self.tiles,
+            goals: self.goals,
+            distances: self.distances,
+        }
+    }
+}
+
+// A whole database built one pattern after another, and installed once the last is done.
+pub struct DatabaseBuilder {
+    key: DatabaseKey,
+    groups: Vec<Vec<usize>>,
+    patterns: Vec<Pattern>,
+    pattern: Option<PatternBuilder>,
+}
+
+impl Build for DatabaseBuilder {
+    fn build(&mut self) -> bool {
+        let (rows, cols, blank_tile, sizes) = self.key;
+        if let Some(pattern) = self.pattern.as_mut() {
+            if pattern.step(BUILD_STATES) {
+                self.patterns.push(self.pattern.take().unwrap().finish());
+            }
+            return false;
+        }
+        if let Some(group) = self.groups.get(self.patterns.len()) {
+            self.pattern = Some(PatternBuilder::new(
+                rows,
+                cols,
+                group.clone(),
+                group.clone(),
+            ));
+            return false;
+        }
+
+        let database = PatternDatabase {
+            rows,
+            cols,
+            patterns: std::mem::take(&mut self.patterns),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        database.save_cached(&PatternDatabase::cache_path(rows, cols, blank_tile, sizes));
+        PatternDatabase::install((rows, cols, blank_tile, sizes), database);
+        true
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.offset + len > self.bytes.len() {
+            return Err("pattern database file is truncated".to_owned());
+        }
+        let slice = &self.bytes[self.offset..(self.offset + len)];
+        self.offset += len;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+}
+
+fn permutations(cells: usize, k: usize) -> usize {
+    (0..k).map(|i| cells - i).product()
+}
+
+// Ranks a placement of k distinct cells among the cells!/(cells-k)! possible ones.
+fn rank(placement: &[usize], cells: usize) -> usize {
+    let mut rank = 0;
+    for (i, cell) in placement.iter().enumerate() {
+        let smaller_taken = placement[..i].iter().filter(|p| *p < cell).count();
+        rank = (rank * (cells - i)) + (cell - smaller_taken);
+    }
+    rank
+}
+
+// Six bits per cell is enough for MAX_CELLS, with the blank's region in the lowest six.
+fn pack(placement: &[usize], blank: usize) -> u64 {
+    let cells = placement
+        .iter()
+        .fold(0, |packed, cell| (packed << 6) | *cell as u64);
+    (cells << 6) | blank as u64
+}
+
+fn unpack(mut packed: u64, placement: &mut [usize]) -> usize {
+    let blank = (packed & 0x3f) as usize;
+    for cell in placement.iter_mut().rev() {
+        packed >>= 6;
+        *cell = (packed & 0x3f) as usize;
+    }
+    blank
+}
+
+fn bit(cell: usize) -> u64 {
+    1 << cell
+}
+
+fn mask(placement: &[usize]) -> u64 {
+    placement.iter().fold(0, |m, cell| m | bit(*cell))
+}
+
+fn mark(visited: &mut [u64], state: usize) {
+    visited[state / 64] |= 1 << (state % 64);
+}
+
+fn is_marked(visited: &[u64], state: usize) -> bool {
+    visited[state / 64] & (1 << (state % 64)) != 0
+}
+
+fn neighbours(cell: usize, rows: usize, cols: usize) -> [Option<usize>; 4] {
+    let (row, col) = (cell / cols, cell % cols);
+    [
+        if col > 0 { Some(cell - 1) } else { None },
+        if col + 1 < cols { Some(cell + 1) } else { None },
+        if row > 0 { Some(cell - cols) } else { None },
+        if row + 1 < rows {
+            Some(cell + cols)
+        } else {
+            None
+        },
+    ]
+}
+
+// Every cell reachable from `start` without crossing an occupied one, grown a step in all four
+// directions at a time. Shifts that would wrap around a row edge are masked off.
+fn flood(start: usize, occupied: u64, rows: usize, cols: usize) -> u64 {
+    let cells = rows * cols;
+    let full = if cells == MAX_CELLS {
+        u64::MAX
+    } else {
+        (1 << cells) - 1
+    };
+    let first_col = (0..rows).fold(0, |m, row| m | bit(row * cols));
+    let last_col = first_col << (cols - 1);
+    let open = full & !occupied;
+
+    let mut region = bit(start);
+    loop {
+        let grown = (region
+            | ((region << 1) & !first_col)
+            | ((region >> 1) & !last_col)
+            | (region << cols)
+            | (region >> cols))
+            & open;
+        if grown == region {
+            return region;
+        }
+        region = grown;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::npuzzle::GenerationMetric;
+
+    #[test]
+    fn lookup_is_admissible_on_3x3() {
+        for seed in 0..200 {
+            let mut board = NBoard::new(3, 3);
+            board.generate_seeded(GenerationMetric::UniformRandom, seed);
+            let blank_tile = board.goal_cell(board.index_at(board.get_missing_index()));
+            let database = PatternDatabase::get(3, 3, blank_tile, &PARTITION_4_4).unwrap();
+            assert!(database.is_compatible(&board));
+            let optimal = board.optimal_distance().unwrap();
+            let estimate = database.distance(&board);
+            assert!(
+                estimate <= optimal,
+                "seed {}: {} > {}",
+                seed,
+                estimate,
+                optimal
+            );
+            assert!(estimate >= board.manhattan_distance(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let groups = PatternDatabase::partition(3, 3, 8, &PARTITION_4_4);
+        let database = PatternDatabase::build(3, 3, &groups).unwrap();
+        let loaded = PatternDatabase::from_bytes(&database.to_bytes()).unwrap();
+        assert_eq!(loaded.to_bytes(), database.to_bytes());
+        assert!(PatternDatabase::from_bytes(&database.to_bytes()[1..]).is_err());
+    }
+
+    #[test]
+    fn builder_installs_the_same_database() {
+        let groups = PatternDatabase::partition(3, 3, 4, &PARTITION_4_4);
+        let mut builder = DatabaseBuilder {
+            key: (3, 3, 4, &PARTITION_4_4),
+            groups: groups.clone(),
+            patterns: Vec::default(),
+            pattern: None,
+        };
+        let mut steps = 1;
+        while !builder.build() {
+            steps += 1;
+        }
+        assert!(steps > groups.len());
+        let installed = PatternDatabase::cached(3, 3, 4, &PARTITION_4_4).unwrap();
+        let database = PatternDatabase::build(3, 3, &groups).unwrap();
+        assert_eq!(installed.to_bytes(), database.to_bytes());
+        assert!(PatternDatabase::builder(3, 3, 4, &PARTITION_4_4).is_none());
+    }
+}
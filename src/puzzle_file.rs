@@ -0,0 +1,448 @@
+/**
+ * @file puzzle_file.rs
+ *
+ * @brief This is the module that reads and writes boards in the plain text n-puzzle format, so
+ * curated puzzle sets can be fed to the app and the solvers.
+ *
+ *   # anything after a '#' is a comment
+ *   3
+ *   1 2 3
+ *   4 0 6
+ *   7 5 8
+ *
+ * The size line holds N for an N x N board, or "M N" for M rows of N columns. Numbers follow the
+ * goal module: tile t is written t + 1 and the blank, which is always the last tile, is written 0.
+ * Boards whose blank is some other tile are renumbered on the way out, so they can still be saved.
+ * Unsolvable boards are rejected, along with why. Custom goals are read from the same format, as the
+ * board that counts as solved.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use crate::goal::Goal;
+use crate::npuzzle::{NBoard, Tile, TileType};
+use std::collections::HashMap;
+
+const SIZE_MIN: usize = 2;
+
+// A whitespace separated word, with the 1-based line and column it starts at.
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl Token<'_> {
+    fn at(&self) -> String {
+        format!("line {}, column {}", self.line, self.column)
+    }
+}
+
+pub fn read(text: &str, goal: &Goal) -> Result<NBoard, String> {
+    let (m, n, cells) = read_cells(text)?;
+    let blank_tile = m * n - 1;
+    let mut board = NBoard::new(m, n);
+    board.set_goal(goal)?;
+    let mut missing_index = 0;
+    let tiles = cells
+        .iter()
+        .enumerate()
+        .map(|(cell, number)| match number {
+            0 => {
+                missing_index = cell;
+                Tile::new(blank_tile, TileType::Missing)
+            }
+            _ => Tile::new(number - 1, TileType::InPlay),
+        })
+        .collect();
+    board.set_board(tiles);
+    board.set_mi(missing_index);
+
+    match board.unsolvable_reason() {
+        Some(reason) => Err(format!("the puzzle can't be solved: {}", reason)),
+        None => Ok(board),
+    }
+}
+
+// A file in the same format read as the solved layout, e.g. the snail goals of the 42-school
+// instances.
+pub fn read_goal(text: &str) -> Result<(usize, usize, Goal), String> {
+    let (m, n, cells) = read_cells(text)?;
+    let blank_tile = m * n - 1;
+    let mut goal = vec![0; m * n];
+    for (cell, number) in cells.iter().enumerate() {
+        match number {
+            0 => goal[blank_tile] = cell,
+            _ => goal[number - 1] = cell,
+        }
+    }
+    Ok((m, n, Goal::Custom(goal)))
+}
+
+// The size and every number, row by row, once each is known to be a tile of the board.
+fn read_cells(text: &str) -> Result<(usize, usize, Vec<usize>), String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, tokenize(i + 1, line)))
+        .filter(|(_, tokens)| !tokens.is_empty());
+
+    let Some((_, size_tokens)) = lines.next() else {
+        return Err("expected the puzzle size, found the end of the file".to_owned());
+    };
+    let (m, n) = parse_size(&size_tokens)?;
+
+    let mut cells = Vec::default();
+    let mut seen: HashMap<usize, String> = HashMap::default();
+    let mut last_line = 1;
+    for (row, (line, tokens)) in lines.enumerate() {
+        last_line = line;
+        if row == m {
+            return Err(format!(
+                "{}: expected {} rows, found more",
+                tokens[0].at(),
+                m
+            ));
+        }
+        if tokens.len() != n {
+            let at = match tokens.get(n) {
+                Some(extra) => extra.at(),
+                None => format!("line {}", line),
+            };
+            return Err(format!(
+                "{}: row {} has {} tiles, expected {}",
+                at,
+                row + 1,
+                tokens.len(),
+                n
+            ));
+        }
+        for token in &tokens {
+            let number = parse_number(token)?;
+            if number >= m * n {
+                return Err(format!(
+                    "{}: tile {} is out of range, a {}x{} puzzle uses 0 to {}",
+                    token.at(),
+                    number,
+                    m,
+                    n,
+                    m * n - 1
+                ));
+            }
+            if let Some(first) = seen.insert(number, token.at()) {
+                return Err(format!(
+                    "{}: tile {} appears twice, first at {}",
+                    token.at(),
+                    number,
+                    first
+                ));
+            }
+            cells.push(number);
+        }
+    }
+    if cells.len() < m * n {
+        return Err(format!(
+            "line {}: expected {} rows, found {}",
+            last_line + 1,
+            m,
+            cells.len() / n
+        ));
+    }
+
+    Ok((m, n, cells))
+}
+
+// Square boards get the single number size line other tools expect.
+pub fn write(board: &NBoard) -> Result<String, String> {
+    let (m, n) = (board.get_m(), board.get_n());
+    let missing_index = board.get_missing_index();
+    if missing_index >= m * n {
+        return Err("the board has no blank yet".to_owned());
+    }
+    let numbers = renumber(board, board.index_at(missing_index));
+
+    let width = (m * n - 1).to_string().len();
+    let mut text = match m == n {
+        true => format!("{}\n", n),
+        false => format!("{} {}\n", m, n),
+    };
+    for row in 0..m {
+        let numbers: Vec<String> = (0..n)
+            .map(|col| {
+                format!(
+                    "{:>width$}",
+                    numbers[board.index_at(row * n + col)],
+                    width = width
+                )
+            })
+            .collect();
+        text += &numbers.join(" ");
+        text += "\n";
+    }
+    Ok(text)
+}
+
+// The number each tile is written as. The file's blank is always the last tile, so any other blank
+// is first slid through the goal to the last tile's goal cell, and every tile takes the number of
+// the goal cell it ends up in. Boards reachable from one goal stay reachable from the other.
+fn renumber(board: &NBoard, blank_tile: usize) -> Vec<usize> {
+    let (m, n) = (board.get_m(), board.get_n());
+    let mut goal_tiles = vec![0; m * n];
+    for tile in 0..m * n {
+        goal_tiles[board.goal_cell(tile)] = tile;
+    }
+
+    let mut cells = goal_tiles.clone();
+    let mut blank = board.goal_cell(blank_tile);
+    let target = board.goal_cell(m * n - 1);
+    while blank != target {
+        let next = match (blank / n).cmp(&(target / n)) {
+            std::cmp::Ordering::Less => blank + n,
+            std::cmp::Ordering::Greater => blank - n,
+            std::cmp::Ordering::Equal if blank % n < target % n => blank + 1,
+            std::cmp::Ordering::Equal => blank - 1,
+        };
+        cells.swap(blank, next);
+        blank = next;
+    }
+
+    let mut numbers = vec![0; m * n];
+    for (cell, tile) in cells.iter().enumerate() {
+        numbers[*tile] = match *tile == blank_tile {
+            true => 0,
+            false => goal_tiles[cell] + 1,
+        };
+    }
+    numbers
+}
+
+fn tokenize(line: usize, text: &str) -> Vec<Token<'_>> {
+    let text = match text.find('#') {
+        Some(comment) => &text[..comment],
+        None => text,
+    };
+    let mut tokens = Vec::default();
+    let mut start: Option<(usize, usize)> = None;
+    for (column, (i, c)) in text.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((i, column + 1)),
+            (true, Some((begin, column))) => {
+                tokens.push(Token {
+                    text: &text[begin..i],
+                    line,
+                    column,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((begin, column)) = start {
+        tokens.push(Token {
+            text: &text[begin..],
+            line,
+            column,
+        });
+    }
+    tokens
+}
+
+fn parse_number(token: &Token) -> Result<usize, String> {
+    token
+        .text
+        .parse()
+        .map_err(|_| format!("{}: expected a number, found '{}'", token.at(), token.text))
+}
+
+fn parse_size(tokens: &[Token]) -> Result<(usize, usize), String> {
+    if let Some(extra) = tokens.get(2) {
+        return Err(format!(
+            "{}: expected the puzzle size, found '{}' after it",
+            extra.at(),
+            extra.text
+        ));
+    }
+    let sizes = tokens
+        .iter()
+        .map(|token| {
+            let size = parse_number(token)?;
+            if size < SIZE_MIN {
+                return Err(format!(
+                    "{}: a puzzle needs at least {} rows and {} columns, found {}",
+                    token.at(),
+                    SIZE_MIN,
+                    SIZE_MIN,
+                    size
+                ));
+            }
+            Ok(size)
+        })
+        .collect::<Result<Vec<usize>, String>>()?;
+    let (m, n) = match sizes[..] {
+        [n] => (n, n),
+        [m, n] => (m, n),
+        _ => unreachable!(),
+    };
+    if m.checked_mul(n).is_none() {
+        return Err(format!(
+            "{}: a {}x{} puzzle is too big",
+            tokens[0].at(),
+            m,
+            n
+        ));
+    }
+    Ok((m, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::npuzzle::GenerationMetric;
+
+    #[test]
+    fn goal_file_reads_as_custom_goal() {
+        let (m, n, goal) = read_goal("3\n1 2 3\n8 0 4\n7 6 5\n").unwrap();
+        assert_eq!((m, n), (3, 3));
+        assert_eq!(goal.cells(3, 3), Goal::Snail.cells(3, 3));
+
+        let board = read("3\n1 2 3\n8 4 0\n7 6 5\n", &goal).unwrap();
+        let mut solved = board.clone();
+        solved.swap(4);
+        assert!(!board.check_win());
+        assert!(solved.check_win());
+    }
+
+    // Slides the blank cell by cell, rows first, the same way renumber does.
+    fn slide_blank(board: &mut NBoard, target: usize) {
+        let n = board.get_n();
+        while board.get_missing_index() != target {
+            let blank = board.get_missing_index();
+            let next = match (blank / n).cmp(&(target / n)) {
+                std::cmp::Ordering::Less => blank + n,
+                std::cmp::Ordering::Greater => blank - n,
+                std::cmp::Ordering::Equal if blank % n < target % n => blank + 1,
+                std::cmp::Ordering::Equal => blank - 1,
+            };
+            board.swap(next);
+        }
+    }
+
+    // Whatever tile the blank is, the board read back is solved by the same moves, followed by
+    // sliding the blank from where the original one belongs to where tile 11 does.
+    #[test]
+    fn any_blank_writes_a_solvable_board() {
+        for goal in [Goal::RowMajor, Goal::BlankFirst, Goal::Snail] {
+            for seed in 0..50 {
+                let mut board = NBoard::new(3, 4);
+                board.set_goal(&goal).unwrap();
+                board.generate_seeded(GenerationMetric::Random(40), seed);
+                let text = write(&board).unwrap();
+                let mut read_back = read(&text, &goal).unwrap();
+                assert_eq!(read_back.get_missing_index(), board.get_missing_index());
+                assert_eq!(write(&read_back).unwrap(), text);
+
+                let blank_tile = board.index_at(board.get_missing_index());
+                if blank_tile == 11 {
+                    assert_eq!(read_back, board);
+                }
+                let steps = board
+                    .weighted_a_star_search(NBoard::manhattan_distance, 1., &mut Default::default())
+                    .unwrap();
+                for step in steps {
+                    read_back.swap(step);
+                }
+                assert_eq!(read_back.get_missing_index(), board.goal_cell(blank_tile));
+                slide_blank(&mut read_back, board.goal_cell(11));
+                assert!(read_back.check_win(), "{:?} seed {}:\n{}", goal, seed, text);
+            }
+        }
+    }
+
+    #[test]
+    fn missing_or_bad_size_is_reported() {
+        let missing = Err("expected the puzzle size, found the end of the file".to_owned());
+        assert_eq!(read_cells(""), missing);
+        assert_eq!(read_cells("# only a comment\n"), missing);
+        assert_eq!(
+            read_cells("1\n"),
+            Err(
+                "line 1, column 1: a puzzle needs at least 2 rows and 2 columns, found 1"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            read_cells("3 3 3\n"),
+            Err("line 1, column 5: expected the puzzle size, found '3' after it".to_owned())
+        );
+    }
+
+    #[test]
+    fn bad_numbers_are_reported_where_they_are() {
+        assert_eq!(
+            read_cells("3\n1 2 3\n4  x 6 # comment\n7 5 8\n"),
+            Err("line 3, column 4: expected a number, found 'x'".to_owned())
+        );
+    }
+
+    #[test]
+    fn duplicate_tiles_are_reported_with_both_positions() {
+        assert_eq!(
+            read_cells("3\n1 2 3\n4 0 6\n7 5 5\n"),
+            Err("line 4, column 5: tile 5 appears twice, first at line 4, column 3".to_owned())
+        );
+    }
+
+    #[test]
+    fn out_of_range_tiles_are_reported() {
+        assert_eq!(
+            read_cells("3\n1 2 3\n4 0 9\n7 5 8\n"),
+            Err("line 3, column 5: tile 9 is out of range, a 3x3 puzzle uses 0 to 8".to_owned())
+        );
+    }
+
+    #[test]
+    fn wrong_column_counts_are_reported() {
+        assert_eq!(
+            read_cells("3\n1 2 3\n4 0 6 9\n7 5 8\n"),
+            Err("line 3, column 7: row 2 has 4 tiles, expected 3".to_owned())
+        );
+        assert_eq!(
+            read_cells("3\n1 2 3\n4 0\n7 5 8\n"),
+            Err("line 3: row 2 has 2 tiles, expected 3".to_owned())
+        );
+    }
+
+    #[test]
+    fn wrong_row_counts_are_reported() {
+        assert_eq!(
+            read_cells("2\n1 2\n3 0\n4 5\n"),
+            Err("line 4, column 1: expected 2 rows, found more".to_owned())
+        );
+        assert_eq!(
+            read_cells("3\n1 2 3\n4 0 6\n"),
+            Err("line 4: expected 3 rows, found 2".to_owned())
+        );
+    }
+
+    #[test]
+    fn unsolvable_boards_explain_why() {
+        assert_eq!(
+            read("3\n2 1 3\n4 5 6\n7 8 0\n", &Goal::RowMajor),
+            Err(
+                "the puzzle can't be solved: it has 1 inversions, an odd number, and on a board 3 \
+                 wide every move keeps their count odd or even"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            read("2\n2 1\n3 0\n", &Goal::RowMajor),
+            Err(
+                "the puzzle can't be solved: it has 1 inversions and the blank is 0 rows from its \
+                 goal row, an odd total, and on a board 2 wide every move keeps that total odd or \
+                 even"
+                    .to_owned()
+            )
+        );
+    }
+}
@@ -11,14 +11,108 @@ use std::mem::swap;
  *
  */
 use super::MAX_WRAP;
+use crate::distance_table::DistanceTable;
+#[cfg(target_arch = "wasm32")]
+use crate::distance_table::TableBuilder;
+use crate::goal::Goal;
 use crate::image_helpers;
 use crate::image_helpers::SubImage;
+use crate::moves::{format_moves, Convention, Move};
 use crate::npuzzle::*;
 #[cfg(target_arch = "wasm32")]
+use crate::search::Build;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::search::CancelToken;
+use crate::search::SearchStatus;
+use crate::search::SolverHandle;
+use crate::solver::{AraStar, DepthLimited, IdaStar, IterativeDeepening, Registry, WeightedAStar};
+use crate::solver_stats::SolverStats;
+use crate::walking_distance::WalkingDistanceTable;
+#[cfg(target_arch = "wasm32")]
 use crate::web_helpers::{isIOS, isMobile};
 
 use chrono::Timelike;
 use log::{debug, error, info};
+#[cfg(not(target_arch = "wasm32"))]
+use poll_promise::Promise;
+use rand::Rng;
+use std::collections::VecDeque;
+
+// How long the agent may think each frame before handing the frame back to the ui.
+#[cfg(target_arch = "wasm32")]
+const SEARCH_SLICE_MS: i64 = 8;
+
+// Past 4x4 nothing optimal finishes while the player waits, so hints and auto-solve fall back to
+// the layered solver there.
+const MAX_SEARCHED_CELLS: usize = 16;
+
+// How long each auto-solve move stays on screen before the next one is played.
+const AUTO_SOLVE_STEP_SECS: f64 = 0.15;
+
+// On native the agent thinks on a worker thread; on the web, where there are no threads, it gets a
+// slice of every frame instead.
+enum AgentSearch {
+    #[cfg(not(target_arch = "wasm32"))]
+    Worker {
+        promise: Promise<(SearchStatus, SolverStats)>,
+        cancel: CancelToken,
+    },
+    #[cfg(target_arch = "wasm32")]
+    Sliced(SolverHandle),
+}
+
+impl AgentSearch {
+    #[allow(unused_mut)]
+    fn spawn(mut handle: SolverHandle) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let cancel = CancelToken::default();
+            let worker_cancel = cancel.clone();
+            let promise = Promise::spawn_thread("solver", move || {
+                let status = handle.run_until_cancelled(&worker_cancel).clone();
+                (status, handle.get_stats())
+            });
+            AgentSearch::Worker { promise, cancel }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            AgentSearch::Sliced(handle)
+        }
+    }
+
+    fn cancel(&mut self) {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            AgentSearch::Worker { cancel, .. } => cancel.cancel(),
+            #[cfg(target_arch = "wasm32")]
+            AgentSearch::Sliced(handle) => handle.cancel(),
+        }
+    }
+
+    // How the search ended, once it has. On the web this is also where it gets its slice.
+    fn poll(&mut self, ctx: &egui::Context) -> Option<(SearchStatus, SolverStats)> {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            AgentSearch::Worker { promise, .. } => {
+                let outcome = promise.ready().cloned();
+                if outcome.is_none() {
+                    ctx.request_repaint();
+                }
+                outcome
+            }
+            #[cfg(target_arch = "wasm32")]
+            AgentSearch::Sliced(handle) => {
+                if handle.is_running() {
+                    handle.step(SEARCH_SLICE_MS);
+                    ctx.request_repaint();
+                    return None;
+                }
+                Some((handle.poll().clone(), handle.get_stats()))
+            }
+        }
+    }
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum GameMode {
@@ -37,6 +131,75 @@ impl std::fmt::Display for GameMode {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Random,
+    // Scrambled to exactly this many moves from the goal.
+    Exact(usize),
+}
+
+// Boards without a distance table are shuffled on the ui thread by IDA*, whose cost doubles every
+// couple of moves deeper. Past these it can hold up a frame for more than a moment, and with only
+// the Manhattan distance to guide it that happens sooner.
+const MAX_EXACT_MOVES: usize = 28;
+const MAX_EXACT_MOVES_MANHATTAN: usize = 24;
+
+impl Difficulty {
+    // Boards with a distance table ready are scrambled to an exact optimal depth, in fifths
+    // of the deepest state. Otherwise the metrics are ordered by the average optimal solution
+    // length each scrambles a 3x3 board to, as checked in npuzzle.rs's tests.
+    pub fn generation_metric(&self, m: usize, n: usize) -> GenerationMetric {
+        if let Some(table) = DistanceTable::ready(m, n, 0) {
+            let deepest = table.get_max_depth();
+            let fifth = |k: usize| (deepest * k) / 5;
+            return match self {
+                Difficulty::Easy => GenerationMetric::OptimalDepth(fifth(1).max(1), fifth(2)),
+                Difficulty::Medium => GenerationMetric::OptimalDepth(fifth(2), fifth(3)),
+                Difficulty::Hard => GenerationMetric::OptimalDepth(fifth(3), fifth(4)),
+                Difficulty::Expert => GenerationMetric::OptimalDepth(fifth(4), deepest),
+                Difficulty::Random => GenerationMetric::UniformRandom,
+                Difficulty::Exact(moves) => GenerationMetric::OptimalDepth(*moves, *moves),
+            };
+        }
+        match self {
+            Difficulty::Easy => GenerationMetric::Random(100),
+            Difficulty::Medium => GenerationMetric::MaxTilesOut,
+            Difficulty::Hard => GenerationMetric::MaxManhattanDistance,
+            Difficulty::Expert => GenerationMetric::MaxEuclideanDistance,
+            Difficulty::Random => GenerationMetric::UniformRandom,
+            Difficulty::Exact(moves) => GenerationMetric::OptimalDepth(*moves, *moves),
+        }
+    }
+
+    // The most moves an Exact scramble of an m x n board can ask for.
+    pub fn max_exact_moves(m: usize, n: usize) -> usize {
+        if let Some(table) = DistanceTable::ready(m, n, 0) {
+            table.get_max_depth()
+        } else if WalkingDistanceTable::supports(m, n) && WalkingDistanceTable::supports(n, m) {
+            MAX_EXACT_MOVES
+        } else {
+            MAX_EXACT_MOVES_MANHATTAN
+        }
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Medium => write!(f, "Medium"),
+            Difficulty::Hard => write!(f, "Hard"),
+            Difficulty::Expert => write!(f, "Expert"),
+            Difficulty::Random => write!(f, "Random"),
+            Difficulty::Exact(moves) => write!(f, "{} Moves", moves),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(default))]
 pub struct PuzzlePanel {
@@ -86,6 +249,14 @@ pub struct PuzzlePanel {
     #[cfg_attr(feature = "serde", serde(skip))]
     pub game_mode: GameMode,
     #[cfg_attr(feature = "serde", serde(skip))]
+    difficulty: Difficulty,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    goal: Goal,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    seed: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_seed: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     force_rebuild: bool,
     #[cfg_attr(feature = "serde", serde(skip))]
     enable_shuffle: bool,
@@ -101,10 +272,61 @@ pub struct PuzzlePanel {
     pub in_win: bool,
     #[cfg_attr(feature = "serde", serde(skip))]
     draw_hint: bool,
+    // cells the blank swapped with since play started, from the board it started on
+    #[cfg_attr(feature = "serde", serde(skip))]
+    played: Vec<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    played_from: NBoard,
+    // moves of a winning run that only went round in a loop
+    #[cfg_attr(feature = "serde", serde(skip))]
+    detour_moves: usize,
+    // fewest moves the board could be solved in when play started, where a distance table knows it
+    #[cfg_attr(feature = "serde", serde(skip))]
+    optimal_moves: Option<usize>,
     #[cfg_attr(feature = "serde", serde(skip))]
     hint_index: usize,
     #[cfg_attr(feature = "serde", serde(skip))]
     outsmart_index: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    registry: Registry,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    solver: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    heuristic: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    weight: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    budget_ms: i64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    depth_limit: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    prune_duplicates: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_search: Option<(String, SolverStats)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    search: Option<AgentSearch>,
+    // the distance table being built a slice of every frame on the web
+    #[cfg(target_arch = "wasm32")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    warm_up: Option<TableBuilder>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    search_agent: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pending_comparison: Option<SolverHandle>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    comparison: Option<AgentSearch>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    comparison_agent: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_comparison: Option<(String, SolverStats)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    hint_board: Option<NBoard>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    auto_solve: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    auto_steps: VecDeque<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    auto_step_time: f64,
 }
 
 impl Default for PuzzlePanel {
@@ -120,7 +342,7 @@ impl Default for PuzzlePanel {
             delay_repaint: false,
             debug_paint: true,
             constrained_width: 0.,
-            board: NBoard::new(3),
+            board: NBoard::new(3, 3),
             regen: false,
             missing_index: (3 * 3) + 1,
             in_play: false,
@@ -133,6 +355,10 @@ impl Default for PuzzlePanel {
             stop_label: "⏹".to_owned(),
             play_bar_button_font_size: 52.,
             game_mode: GameMode::TimeAttack,
+            difficulty: Difficulty::Easy,
+            goal: Goal::default(),
+            seed: None,
+            last_seed: None,
             force_rebuild: true,
             enable_shuffle: true,
             timer_start: chrono::DateTime::default(),
@@ -141,8 +367,32 @@ impl Default for PuzzlePanel {
             timer_color: egui::Color32::RED,
             in_win: false,
             draw_hint: false,
+            played: Vec::default(),
+            played_from: NBoard::default(),
+            detour_moves: 0,
+            optimal_moves: None,
             hint_index: 0,
             outsmart_index: 0,
+            registry: Registry::default(),
+            solver: "A*".to_owned(),
+            heuristic: "Manhattan Distance".to_owned(),
+            weight: AraStar::default().weight,
+            budget_ms: AraStar::default().budget_ms,
+            depth_limit: DepthLimited::default().depth_limit,
+            prune_duplicates: DepthLimited::default().prune_duplicates,
+            last_search: None,
+            search: None,
+            #[cfg(target_arch = "wasm32")]
+            warm_up: None,
+            search_agent: String::default(),
+            pending_comparison: None,
+            comparison: None,
+            comparison_agent: String::default(),
+            last_comparison: None,
+            hint_board: None,
+            auto_solve: false,
+            auto_steps: VecDeque::default(),
+            auto_step_time: 0.,
         }
     }
 }
@@ -158,8 +408,13 @@ impl PuzzlePanel {
                 self.timer_color = egui::Color32::GOLD;
                 self.in_win = true;
                 self.draw_hint = false;
+                self.detour_moves = self.count_detours();
             }
         }
+        self.advance_search(ctx);
+        self.advance_auto_solve(ctx);
+        #[cfg(target_arch = "wasm32")]
+        self.warm_up_tables(ctx);
     }
 
     pub fn ui(&mut self, ui: &mut egui::Ui, frame: &mut eframe::Frame) {
@@ -182,7 +437,7 @@ impl PuzzlePanel {
 
         let avail_w = ui.available_width() - ui.ctx().style().spacing.window_margin.left;
 
-        let mut button_side = (avail_w / (self.m as f32)) - ui.ctx().style().spacing.item_spacing.y;
+        let mut button_side = (avail_w / (self.n as f32)) - ui.ctx().style().spacing.item_spacing.y;
 
         // we first assume we don't need to offset the starting x coord of our puzzle_panel
         let mut w_offset = 0.;
@@ -202,16 +457,16 @@ impl PuzzlePanel {
         self.constrained_width = avail_w;
         // we need to fit our puzzle_panel with the settings_panel, thus we must fix dimensions
         // and set the proper x coord offset if we don't fit by avail_w
-        if avail_w > avail_h {
+        if (avail_w / (self.n as f32)) > (avail_h / (self.m as f32)) {
             button_side = (avail_h / (self.m as f32)) - ui.ctx().style().spacing.item_spacing.y;
             w_offset = (avail_w
-                - ((self.m as f32) * (button_side + ui.ctx().style().spacing.item_spacing.y)))
+                - ((self.n as f32) * (button_side + ui.ctx().style().spacing.item_spacing.y)))
                 / 2.;
 
             w_offset += (ui.ctx().style().spacing.item_spacing.y * 3.)
-                - (((6. - self.m as f32) / 4.) * ui.ctx().style().spacing.item_spacing.y);
+                - (((6. - self.n as f32) / 4.) * ui.ctx().style().spacing.item_spacing.y);
 
-            self.constrained_width = avail_h;
+            self.constrained_width = avail_h * (self.n as f32) / (self.m as f32);
         }
         if self.in_play {
             ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
@@ -229,10 +484,30 @@ impl PuzzlePanel {
                                 .size(50.)
                                 .color(self.timer_color),
                         );
+                        if self.in_win {
+                            let moves_made = self.played.len();
+                            let mut feedback = match self
+                                .optimal_moves
+                                .map(|optimal| moves_made.saturating_sub(optimal))
+                            {
+                                Some(0) => format!("{} moves, an optimal solution!", moves_made),
+                                Some(extra) => {
+                                    format!("{} moves, {} more than optimal", moves_made, extra)
+                                }
+                                None => format!("{} moves", moves_made),
+                            };
+                            if self.detour_moves > 0 {
+                                feedback +=
+                                    &format!("\n{} of them went round in loops", self.detour_moves);
+                            }
+                            ui.label(egui::RichText::new(feedback).size(18.));
+                        }
                     } else {
                         ui.add_space(25.);
                         if !self.in_win {
-                            if ui
+                            if self.is_searching() {
+                                ui.add_sized([200., 50.], egui::Spinner::new().size(32.));
+                            } else if ui
                                 .add_sized(
                                     [200., 50.],
                                     egui::Button::new(
@@ -241,7 +516,8 @@ impl PuzzlePanel {
                                 )
                                 .clicked()
                             {
-                                let mut new_board = crate::npuzzle::NBoard::new(self.n as usize);
+                                let mut new_board =
+                                    crate::npuzzle::NBoard::new(self.m as usize, self.n as usize);
                                 let mut tiles: Vec<crate::npuzzle::Tile> = Vec::default();
                                 let mut mi = 0;
                                 for i in 0..self.puzzle_subimages.len() {
@@ -390,31 +666,31 @@ impl PuzzlePanel {
                                     }
 
                                     // is below
-                                    if ((subimage_index as i32) - self.m) >= 0 {
+                                    if ((subimage_index as i32) - self.n) >= 0 {
                                         if let Some(subimage) = self
                                             .puzzle_subimages
-                                            .get(((subimage_index as i32) - self.m) as usize)
+                                            .get(((subimage_index as i32) - self.n) as usize)
                                         {
                                             if subimage.contains(pos) {
                                                 swap = true;
                                                 swap_ind =
-                                                    ((subimage_index as i32) - self.m) as usize;
+                                                    ((subimage_index as i32) - self.n) as usize;
                                             }
                                         }
                                     }
 
                                     // is above
-                                    if ((subimage_index as i32) + self.m)
+                                    if ((subimage_index as i32) + self.n)
                                         < (self.puzzle_subimages.len() as i32)
                                     {
                                         if let Some(subimage) = self
                                             .puzzle_subimages
-                                            .get(((subimage_index as i32) + self.m) as usize)
+                                            .get(((subimage_index as i32) + self.n) as usize)
                                         {
                                             if subimage.contains(pos) {
                                                 swap = true;
                                                 swap_ind =
-                                                    ((subimage_index as i32) + self.m) as usize;
+                                                    ((subimage_index as i32) + self.n) as usize;
                                             }
                                         }
                                     }
@@ -439,6 +715,7 @@ impl PuzzlePanel {
                                             self.puzzle_subimages
                                                 .swap(missing_index_swap, subimage_index);
                                             self.draw_hint = false;
+                                            self.played.push(subimage_index);
                                         }
                                     }
                                 }
@@ -520,6 +797,9 @@ impl PuzzlePanel {
                         self.timer_accum = 0.0;
                         self.in_win = false;
                         self.timer_color = egui::Color32::RED;
+                        self.played.clear();
+                        self.played_from = self.board.clone();
+                        self.optimal_moves = self.board.optimal_distance();
                     } else {
                         self.in_win = false;
                     }
@@ -547,35 +827,42 @@ impl PuzzlePanel {
                     }
                     self.in_play = false;
                     self.has_shuffled = false;
+                    self.cancel_search();
                 }
             });
             ui.scope(|ui| {
                 let enabled =
                     (self.in_play && !self.in_win && self.game_mode != GameMode::Outsmart);
                 ui.set_enabled(enabled);
-                if ui
+                // while the agent thinks the hint button spins, and clicking it calls the search off
+                let searching = self.is_searching();
+                let hint_label = if searching {
+                    ""
+                } else {
+                    self.hint_label.as_str()
+                };
+                let response = ui
                     .add_sized(
                         [bw, bh],
                         egui::Button::new(
-                            egui::RichText::new(&self.hint_label)
-                                .size(self.play_bar_button_font_size),
+                            egui::RichText::new(hint_label).size(self.play_bar_button_font_size),
                         ),
                     )
-                    .clicked()
-                {
-                    self.draw_hint = true;
-
-                    if let Some(solution) = self.board.a_star_solve() {
-                        for i in 0..solution.len() {
-                            if let Some(next_move) = solution.get(i) {
-                                let next = self.board.index_at(*next_move);
-                                if i == 0 {
-                                    self.hint_index = self.find_subimage_from_tile_index(next);
-                                }
-                                debug!("{next}");
-                            }
-                        }
-                        debug!("================================");
+                    .on_hover_text("Right click to watch the agent solve it");
+                if searching {
+                    ui.put(
+                        response.rect,
+                        egui::Spinner::new().size(self.play_bar_button_font_size),
+                    );
+                }
+                if response.clicked() || response.secondary_clicked() {
+                    if searching {
+                        self.cancel_search();
+                    } else {
+                        let board = self.board.clone();
+                        self.start_search(&board);
+                        self.hint_board = Some(board);
+                        self.auto_solve = response.secondary_clicked();
                     }
                 }
             });
@@ -593,7 +880,6 @@ impl PuzzlePanel {
                     .clicked()
                 {
                     if self.game_mode == GameMode::TimeAttack {
-                        self.board.generate();
                         self.regen = true;
                         self.enable_shuffle = false;
                         self.has_shuffled = true;
@@ -615,6 +901,7 @@ impl PuzzlePanel {
                 self.in_play = false;
                 self.enable_shuffle = true;
                 self.has_shuffled = false;
+                self.cancel_search();
                 if self.game_mode == GameMode::Outsmart {
                     self.in_win = false;
                     self.outsmart_index = 0;
@@ -678,29 +965,128 @@ impl PuzzlePanel {
     }
 
     fn generate_puzzle_board(&mut self) {
+        self.cancel_search();
         self.reset_board();
-        self.board.generate();
+        // without a fixed seed every shuffle draws a fresh one, which is kept so it can be shared
+        let seed = match self.seed {
+            Some(seed) => seed,
+            None => rand::thread_rng().gen(),
+        };
+        let (m, n) = (self.m as usize, self.n as usize);
+        match self.difficulty {
+            Difficulty::Exact(moves) => {
+                let mut rng = NBoard::seeded_rng(seed);
+                if let Err(e) = self.board.generate_at_depth(moves, moves, &mut rng) {
+                    // a blank that starts elsewhere can leave the deepest states out of reach
+                    error!("{}", e);
+                    let metric = Difficulty::Expert.generation_metric(m, n);
+                    self.board.generate_with_rng(metric, &mut rng);
+                }
+            }
+            difficulty => self
+                .board
+                .generate_seeded(difficulty.generation_metric(m, n), seed),
+        }
+        self.last_seed = Some(seed);
+        self.arrange_subimages();
+
+        debug!("seed: {}", seed);
+        debug!("solvable: {}", self.board.solvable());
+        debug!("missing: {}", self.missing_index);
+        debug! {"state: \n{}", self.board};
+    }
+
+    // Loads a board read from a puzzle file in place of a shuffle, so it has to fit the current grid.
+    pub fn load_board(&mut self, board: NBoard) -> Result<(), String> {
+        if !self.enable_shuffle || self.game_mode != GameMode::TimeAttack {
+            return Err("reset the puzzle in Time Attack before loading a board".to_owned());
+        }
+        if (board.get_m(), board.get_n()) != (self.m as usize, self.n as usize) {
+            return Err(format!(
+                "the file holds a {}x{} puzzle, but the grid is {}x{}",
+                board.get_m(),
+                board.get_n(),
+                self.m,
+                self.n
+            ));
+        }
+        if self.puzzle_subimages.len() != self.m as usize * self.n as usize {
+            return Err("the puzzle image isn't ready yet".to_owned());
+        }
+
+        self.cancel_search();
+        self.board = board;
+        self.last_seed = None;
+        self.arrange_subimages();
+        self.enable_shuffle = false;
+        self.has_shuffled = true;
+        debug! {"loaded: \n{}", self.board};
+        Ok(())
+    }
+
+    pub fn get_board_text(&self) -> Result<String, String> {
+        self.board.to_text()
+    }
+
+    // Plays a move list from the Debug Menu as if each tile had been slid by hand, so it counts
+    // towards the run. Nothing is moved unless every move stays on the board.
+    pub fn apply_moves(&mut self, moves: &[Move]) -> Result<(), String> {
+        if !self.in_play || self.in_win || self.game_mode != GameMode::TimeAttack {
+            return Err("start a Time Attack puzzle before applying moves".to_owned());
+        }
+        for index in self.board.indices_from_moves(moves)? {
+            self.play_step(index);
+        }
+        Ok(())
+    }
+
+    // Slides the tile at `index` into the blank, as a drag would.
+    fn play_step(&mut self, index: usize) {
+        let missing_index_swap = self.board.get_missing_index();
+        self.missing_index = self.board.swap(index);
+        self.puzzle_subimages.swap(missing_index_swap, index);
+        self.played.push(index);
+        self.draw_hint = false;
+    }
+
+    // Plays the auto-solve a move at a time, so it can be followed.
+    fn advance_auto_solve(&mut self, ctx: &egui::Context) {
+        if self.auto_steps.is_empty() {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        if now < self.auto_step_time {
+            return;
+        }
+        self.auto_step_time = now + AUTO_SOLVE_STEP_SECS;
+        match self.auto_steps.pop_front() {
+            Some(index) if self.in_play && self.board.get_swappable().contains(&index) => {
+                self.play_step(index)
+            }
+            // the player paused or moved a tile themselves, so the rest no longer fits
+            _ => self.auto_steps.clear(),
+        }
+    }
 
+    // Orders the solved grid's subimages the way the board's tiles are laid out. Every tile shows the
+    // piece of the picture under its goal cell, so the picture is whole on every goal.
+    fn arrange_subimages(&mut self) {
         let mut new_subimages: Vec<image_helpers::SubImage> = Vec::default();
         for i in 0..self.puzzle_subimages.len() {
-            let tile_index = self.board.index_at(i);
-            if let Some(simg) = self.puzzle_subimages.get(tile_index) {
+            let goal_cell = self.board.goal_cell(self.board.index_at(i));
+            if let Some(simg) = self.puzzle_subimages.get(goal_cell) {
                 new_subimages.insert(new_subimages.len(), simg.clone())
             }
         }
 
         self.puzzle_subimages = new_subimages;
         self.missing_index = self.board.get_missing_index();
-
-        debug!("solvable: {}", self.board.solvable());
-        debug!("missing: {}", self.missing_index);
-        debug! {"state: \n{}", self.board};
     }
 
     #[cfg(target_arch = "wasm32")]
     fn fix_puzzle_offset_for_mobile(&self, ui: &mut egui::Ui) {
         if isMobile() || isIOS() {
-            let x = self.m as f32;
+            let x = self.n as f32;
             let input_min = 2.0;
             let input_max = 6.0;
             let output_min = 1.0;
@@ -737,6 +1123,7 @@ impl PuzzlePanel {
                 rimg.texture_id(ui.ctx()),
                 subimage_index,
                 self.m,
+                self.n,
                 x,
                 y,
                 button_side,
@@ -772,16 +1159,265 @@ impl PuzzlePanel {
         self.puzzle_subimages.len()
     }
 
-    pub fn set_mn(&mut self, mn: i32) {
-        self.m = mn;
-        self.n = mn;
+    pub fn set_mn(&mut self, m: i32, n: i32) {
+        self.m = m;
+        self.n = n;
         self.rebuild_on_next_frame();
     }
 
+    pub fn get_solver_names(&self) -> Vec<String> {
+        self.registry.solver_names()
+    }
+
+    pub fn get_heuristic_names(&self) -> Vec<String> {
+        self.registry.heuristic_names()
+    }
+
+    pub fn solver_uses_heuristic(&self, solver: &str) -> bool {
+        match self.registry.solver(solver) {
+            Some(solver) => solver.uses_heuristic(),
+            None => true,
+        }
+    }
+
+    pub fn solver_uses_weight(&self, solver: &str) -> bool {
+        match self.registry.solver(solver) {
+            Some(solver) => solver.uses_weight(),
+            None => false,
+        }
+    }
+
+    pub fn solver_uses_time_budget(&self, solver: &str) -> bool {
+        match self.registry.solver(solver) {
+            Some(solver) => solver.uses_time_budget(),
+            None => false,
+        }
+    }
+
+    pub fn solver_uses_depth_limit(&self, solver: &str) -> bool {
+        match self.registry.solver(solver) {
+            Some(solver) => solver.uses_depth_limit(),
+            None => false,
+        }
+    }
+
+    pub fn solver_uses_move_pruning(&self, solver: &str) -> bool {
+        match self.registry.solver(solver) {
+            Some(solver) => solver.uses_move_pruning(),
+            None => false,
+        }
+    }
+
+    pub fn set_agent_parameters(
+        &mut self,
+        weight: f64,
+        budget_ms: i64,
+        depth_limit: usize,
+        prune_duplicates: bool,
+    ) {
+        if self.weight == weight
+            && self.budget_ms == budget_ms
+            && self.depth_limit == depth_limit
+            && self.prune_duplicates == prune_duplicates
+        {
+            return;
+        }
+        self.weight = weight;
+        self.budget_ms = budget_ms;
+        self.depth_limit = depth_limit;
+        self.prune_duplicates = prune_duplicates;
+        self.registry
+            .register_solver(Box::new(WeightedAStar { weight }));
+        self.registry
+            .register_solver(Box::new(AraStar { weight, budget_ms }));
+        self.registry
+            .register_solver(Box::new(IdaStar { prune_duplicates }));
+        self.registry.register_solver(Box::new(DepthLimited {
+            depth_limit,
+            prune_duplicates,
+        }));
+        self.registry.register_solver(Box::new(IterativeDeepening {
+            depth_limit,
+            prune_duplicates,
+        }));
+    }
+
+    fn start_search(&mut self, board: &NBoard) {
+        self.cancel_search();
+        let solver = match board.get_m() * board.get_n() > MAX_SEARCHED_CELLS {
+            true => "Layered".to_owned(),
+            false => self.solver.clone(),
+        };
+        self.search_agent = self.agent_name(&solver);
+        self.hint_board = None;
+        self.last_comparison = None;
+        // the solver it is compared with waits its turn, so the two never compete for the frame
+        if let Some(compared) = self.registry.compared_with(&solver).map(str::to_owned) {
+            self.comparison_agent = self.agent_name(&compared);
+            self.pending_comparison = Some(self.registry.start(board, &compared, &self.heuristic));
+        }
+        let handle = self.registry.start(board, &solver, &self.heuristic);
+        self.search = Some(AgentSearch::spawn(handle));
+    }
+
+    fn agent_name(&self, solver: &str) -> String {
+        if self.solver_uses_heuristic(solver) {
+            format!("{} ({})", solver, self.heuristic)
+        } else {
+            solver.to_owned()
+        }
+    }
+
+    fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    fn cancel_search(&mut self) {
+        self.pending_comparison = None;
+        self.auto_steps.clear();
+        for search in [self.search.as_mut(), self.comparison.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            search.cancel();
+        }
+    }
+
+    // Building a distance table the moment a shuffle needs one would freeze the page, so on the web
+    // every table for the current size is built ahead of time, a slice of each frame. Shuffles fall
+    // back to the larger boards' metrics until theirs is ready.
+    #[cfg(target_arch = "wasm32")]
+    fn warm_up_tables(&mut self, ctx: &egui::Context) {
+        let (m, n) = (self.m as usize, self.n as usize);
+        if self.warm_up.is_none() {
+            self.warm_up =
+                (0..m * n).find_map(|blank_tile| DistanceTable::builder(m, n, blank_tile));
+        }
+        let Some(builder) = self.warm_up.as_mut() else {
+            return;
+        };
+        let started = chrono::Utc::now();
+        while chrono::Utc::now() - started < chrono::Duration::milliseconds(SEARCH_SLICE_MS) {
+            if builder.build() {
+                self.warm_up = None;
+                break;
+            }
+        }
+        ctx.request_repaint();
+    }
+
+    fn advance_search(&mut self, ctx: &egui::Context) {
+        if let Some((status, stats)) = self.search.as_mut().and_then(|search| search.poll(ctx)) {
+            self.search = None;
+            self.finish_search(status, stats);
+        }
+        if let Some((status, stats)) = self.comparison.as_mut().and_then(|search| search.poll(ctx))
+        {
+            self.comparison = None;
+            let agent = match status {
+                SearchStatus::Cancelled => format!("{} (cancelled)", self.comparison_agent),
+                _ => self.comparison_agent.clone(),
+            };
+            self.last_comparison = Some((agent, stats));
+        }
+    }
+
+    fn finish_search(&mut self, status: SearchStatus, stats: SolverStats) {
+        let agent = match status {
+            SearchStatus::Cancelled => format!("{} (cancelled)", self.search_agent),
+            _ => self.search_agent.clone(),
+        };
+        debug!("{}: {}", agent, stats);
+        self.last_search = Some((agent, stats));
+        if let (SearchStatus::Solved(_), Some(handle)) = (&status, self.pending_comparison.take()) {
+            self.comparison = Some(AgentSearch::spawn(handle));
+        }
+
+        // a hint for a board the player has since moved on from is no use
+        if let (Some(hint_board), SearchStatus::Solved(solution)) = (self.hint_board.take(), status)
+        {
+            // depth-first solvers wander, so their detours are cut before the hint is taken
+            let solution = hint_board
+                .moves_from_indices(&solution)
+                .and_then(|moves| hint_board.simplify_moves(&moves))
+                .and_then(|moves| {
+                    debug!("solution: {}", format_moves(&moves, Convention::BlankMoves));
+                    hint_board.indices_from_moves(&moves)
+                })
+                .unwrap_or_else(|e| {
+                    error!("{}", e);
+                    solution
+                });
+            if hint_board != self.board {
+                return;
+            }
+            if self.auto_solve {
+                self.auto_steps = solution.into();
+            } else if let Some(next_move) = solution.first() {
+                let next = self.board.goal_cell(self.board.index_at(*next_move));
+                self.hint_index = self.find_subimage_from_tile_index(next);
+                self.draw_hint = true;
+            }
+        }
+    }
+
+    // Checks the winning run really was played from the starting board before counting its loops.
+    fn count_detours(&self) -> usize {
+        let detours = self
+            .played_from
+            .moves_from_indices(&self.played)
+            .and_then(|moves| {
+                self.played_from.verify_moves(&moves)?;
+                self.played_from.simplify_moves(&moves)
+            })
+            .map(|shortened| self.played.len() - shortened.len());
+        match detours {
+            Ok(detours) => detours,
+            Err(e) => {
+                error!("the recorded moves don't match the board: {}", e);
+                0
+            }
+        }
+    }
+
+    pub fn get_last_search(&self) -> Option<(String, SolverStats)> {
+        self.last_search.clone()
+    }
+
+    // What the solver the last search is compared with did on the same board.
+    pub fn get_last_comparison(&self) -> Option<(String, SolverStats)> {
+        self.last_comparison.clone()
+    }
+
+    pub fn set_agent(&mut self, solver: String, heuristic: String) {
+        self.solver = solver;
+        self.heuristic = heuristic;
+    }
+
     pub fn set_game_mode(&mut self, game_mode: GameMode) {
         self.game_mode = game_mode;
     }
 
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    // A new goal starts the grid over, just like a new size does.
+    pub fn set_goal(&mut self, goal: Goal) {
+        if goal != self.goal {
+            self.goal = goal;
+            self.rebuild_on_next_frame();
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    pub fn get_last_seed(&self) -> Option<u64> {
+        self.last_seed
+    }
+
     pub fn get_constrained_width(&self) -> f32 {
         self.constrained_width
     }
@@ -806,7 +1442,10 @@ impl PuzzlePanel {
     }
 
     fn reset_board(&mut self) {
-        self.board = NBoard::new(self.n as usize);
+        self.board = NBoard::new(self.m as usize, self.n as usize);
+        if let Err(e) = self.board.set_goal(&self.goal) {
+            error!("{}", e);
+        }
     }
 
     fn calc_play_bar_ui_height(&mut self, ui: &egui::Ui) -> f32 {
@@ -859,7 +1498,8 @@ pub fn build_next_subimage(
     id: String,
     tid: egui::TextureId,
     ind: usize,
-    mn: i32,
+    m: i32,
+    n: i32,
     x: i32,
     y: i32,
     button_side: f32,
@@ -867,10 +1507,10 @@ pub fn build_next_subimage(
     let next_region = get_next_subimage_region(ui, button_side);
     let sub_image_rect = egui::Rect::from_two_pos(next_region.min, next_region.max);
 
-    let u_x = (x as f32) * (1.0 / (mn as f32));
-    let u_y = ((x + 1) as f32) * (1.0 / (mn as f32));
-    let v_x = (y as f32) * (1.0 / (mn as f32));
-    let v_y = ((y + 1) as f32) * (1.0 / (mn as f32));
+    let u_x = (x as f32) * (1.0 / (n as f32));
+    let u_y = ((x + 1) as f32) * (1.0 / (n as f32));
+    let v_x = (y as f32) * (1.0 / (m as f32));
+    let v_y = ((y + 1) as f32) * (1.0 / (m as f32));
 
     let uv_rect = egui::Rect::from_x_y_ranges(
         std::ops::RangeInclusive::new(u_x, u_y),
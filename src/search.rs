@@ -0,0 +1,351 @@
+/**
+ * @file search.rs
+ *
+ * @brief This is the module that lets a search be advanced a slice at a time, so the ui can spend a
+ * few milliseconds of every frame on it instead of freezing until it's done.
+ *
+ * A search only has to implement Expand, doing one small unit of work per call. SolverHandle drives
+ * it against a time budget, keeps its SolverStats, and can cancel it. A handle runs on whichever
+ * thread steps it: the ui thread on the web, a worker that watches a CancelToken on native.
+ *
+ * Tables a search needs that haven't been built yet are built the same way, a small Build step per
+ * call, before its first expansion.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use crate::solver_stats::SolverStats;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// Checking the clock after every expansion would cost more than some expansions do.
+const CLOCK_INTERVAL: usize = 64;
+// How long a worker may go without noticing it was cancelled.
+const CANCEL_POLL_MS: i64 = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SearchStatus {
+    Running,
+    Solved(Vec<usize>),
+    Failed,
+    Cancelled,
+}
+
+pub trait Expand {
+    // One unit of work, usually a single node expansion.
+    fn expand(&mut self, stats: &mut SolverStats) -> SearchStatus;
+}
+
+// Work that has to be done before a search can start, such as building the tables behind its
+// heuristic, cut into pieces about as small as an expansion.
+pub trait Build {
+    // One piece of the work, returning true once there is none left.
+    fn build(&mut self) -> bool;
+}
+
+// A search that first works through its builds.
+pub struct Prepared {
+    builds: Vec<Box<dyn Build + Send>>,
+    search: Box<dyn Expand + Send>,
+}
+
+impl Prepared {
+    pub fn new(builds: Vec<Box<dyn Build + Send>>, search: Box<dyn Expand + Send>) -> Self {
+        Self { builds, search }
+    }
+}
+
+impl Expand for Prepared {
+    fn expand(&mut self, stats: &mut SolverStats) -> SearchStatus {
+        match self.builds.first_mut() {
+            Some(build) => {
+                if build.build() {
+                    self.builds.remove(0);
+                }
+                SearchStatus::Running
+            }
+            None => self.search.expand(stats),
+        }
+    }
+}
+
+pub fn run_to_completion(search: &mut impl Expand, stats: &mut SolverStats) -> Option<Vec<usize>> {
+    loop {
+        match search.expand(stats) {
+            SearchStatus::Running => continue,
+            SearchStatus::Solved(steps) => return Some(steps),
+            SearchStatus::Failed | SearchStatus::Cancelled => return None,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+pub struct SolverHandle {
+    search: Option<Box<dyn Expand + Send>>,
+    status: SearchStatus,
+    stats: SolverStats,
+    // time spent inside step, not since the search was started
+    elapsed_us: i64,
+}
+
+impl SolverHandle {
+    pub fn new(search: Box<dyn Expand + Send>) -> Self {
+        Self {
+            search: Some(search),
+            status: SearchStatus::Running,
+            stats: SolverStats::default(),
+            elapsed_us: 0,
+        }
+    }
+
+    // For solvers that can't be sliced and were run to completion up front.
+    pub fn finished(steps: Option<Vec<usize>>, stats: SolverStats) -> Self {
+        Self {
+            search: None,
+            status: match steps {
+                Some(steps) => SearchStatus::Solved(steps),
+                None => SearchStatus::Failed,
+            },
+            stats,
+            elapsed_us: 0,
+        }
+    }
+
+    // Works on the search for roughly `budget_ms`, returning early once it finishes.
+    pub fn step(&mut self, budget_ms: i64) -> &SearchStatus {
+        self.advance(Some(chrono::Duration::milliseconds(budget_ms)));
+        &self.status
+    }
+
+    #[allow(unused)]
+    pub fn run(&mut self) -> &SearchStatus {
+        self.advance(None);
+        &self.status
+    }
+
+    // Runs to completion unless `cancel` is set first.
+    pub fn run_until_cancelled(&mut self, cancel: &CancelToken) -> &SearchStatus {
+        while self.is_running() {
+            if cancel.is_cancelled() {
+                self.cancel();
+                break;
+            }
+            self.step(CANCEL_POLL_MS);
+        }
+        &self.status
+    }
+
+    #[allow(unused)]
+    pub fn poll(&self) -> &SearchStatus {
+        &self.status
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == SearchStatus::Running
+    }
+
+    // Drops the search and everything it was holding on to.
+    pub fn cancel(&mut self) {
+        if self.is_running() {
+            self.search = None;
+            self.status = SearchStatus::Cancelled;
+            self.stats
+                .finish(chrono::Duration::microseconds(self.elapsed_us), None);
+        }
+    }
+
+    #[allow(unused)]
+    pub fn get_solution(&self) -> Option<Vec<usize>> {
+        match &self.status {
+            SearchStatus::Solved(steps) => Some(steps.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn get_stats(&self) -> SolverStats {
+        self.stats.clone()
+    }
+
+    fn advance(&mut self, budget: Option<chrono::Duration>) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+
+        let started = chrono::Utc::now();
+        let mut status = SearchStatus::Running;
+        let mut calls = 0;
+        while status == SearchStatus::Running {
+            status = search.expand(&mut self.stats);
+            calls += 1;
+            if let Some(budget) = budget {
+                if calls % CLOCK_INTERVAL == 0 && chrono::Utc::now() - started >= budget {
+                    break;
+                }
+            }
+        }
+        self.elapsed_us += (chrono::Utc::now() - started)
+            .num_microseconds()
+            .unwrap_or(0);
+
+        if status != SearchStatus::Running {
+            self.search = None;
+            let steps = match &status {
+                SearchStatus::Solved(steps) => Some(steps),
+                _ => None,
+            };
+            self.stats
+                .finish(chrono::Duration::microseconds(self.elapsed_us), steps);
+        }
+        self.status = status;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    // Solved after `remaining` more expansions, each taking `pause`.
+    struct Countdown {
+        remaining: usize,
+        pause: Duration,
+    }
+
+    impl Countdown {
+        fn new(remaining: usize, pause_us: u64) -> Self {
+            Self {
+                remaining,
+                pause: Duration::from_micros(pause_us),
+            }
+        }
+    }
+
+    impl Expand for Countdown {
+        fn expand(&mut self, stats: &mut SolverStats) -> SearchStatus {
+            stats.expanded += 1;
+            std::thread::sleep(self.pause);
+            if self.remaining == 0 {
+                return SearchStatus::Solved(vec![0, 1, 2]);
+            }
+            self.remaining -= 1;
+            SearchStatus::Running
+        }
+    }
+
+    #[test]
+    fn step_stays_within_its_budget() {
+        let mut handle = SolverHandle::new(Box::new(Countdown::new(usize::MAX, 50)));
+        let started = Instant::now();
+        assert_eq!(handle.step(5), &SearchStatus::Running);
+        // the clock is only read every CLOCK_INTERVAL expansions
+        assert!(started.elapsed() < Duration::from_millis(100));
+        let expanded = handle.get_stats().expanded;
+        assert!(expanded > 0);
+        assert_eq!(expanded % CLOCK_INTERVAL, 0);
+
+        handle.step(5);
+        assert!(handle.get_stats().expanded > expanded);
+    }
+
+    #[test]
+    fn step_returns_once_solved() {
+        let mut handle = SolverHandle::new(Box::new(Countdown::new(10, 0)));
+        assert_eq!(handle.step(1000), &SearchStatus::Solved(vec![0, 1, 2]));
+        assert_eq!(handle.get_solution(), Some(vec![0, 1, 2]));
+        let stats = handle.get_stats();
+        assert_eq!((stats.expanded, stats.depth), (11, Some(3)));
+
+        handle.step(1000);
+        assert_eq!(handle.get_stats().expanded, 11);
+    }
+
+    #[test]
+    fn cancel_drops_the_search() {
+        let mut handle = SolverHandle::new(Box::new(Countdown::new(usize::MAX, 0)));
+        handle.step(1);
+        handle.cancel();
+        assert_eq!(handle.poll(), &SearchStatus::Cancelled);
+        assert!(!handle.is_running());
+        assert_eq!(handle.get_solution(), None);
+
+        let expanded = handle.get_stats().expanded;
+        handle.step(1);
+        assert_eq!(handle.get_stats().expanded, expanded);
+    }
+
+    #[test]
+    fn run_until_cancelled_stops_when_told() {
+        let cancel = CancelToken::default();
+        let worker_cancel = cancel.clone();
+        let worker = std::thread::spawn(move || {
+            let mut handle = SolverHandle::new(Box::new(Countdown::new(usize::MAX, 10)));
+            handle.run_until_cancelled(&worker_cancel).clone()
+        });
+        std::thread::sleep(Duration::from_millis(20));
+        cancel.cancel();
+        assert_eq!(worker.join().unwrap(), SearchStatus::Cancelled);
+
+        let mut handle = SolverHandle::new(Box::new(Countdown::new(100, 0)));
+        let status = handle.run_until_cancelled(&CancelToken::default());
+        assert_eq!(status, &SearchStatus::Solved(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn finished_handles_have_nothing_left_to_do() {
+        let stats = SolverStats {
+            expanded: 7,
+            ..Default::default()
+        };
+        let mut handle = SolverHandle::finished(Some(vec![3]), stats.clone());
+        assert!(!handle.is_running());
+        assert_eq!(handle.step(1), &SearchStatus::Solved(vec![3]));
+        assert_eq!(handle.get_stats(), stats);
+
+        let handle = SolverHandle::finished(None, SolverStats::default());
+        assert_eq!(handle.poll(), &SearchStatus::Failed);
+        assert_eq!(handle.get_solution(), None);
+    }
+
+    // Done after `remaining` more steps.
+    struct Steps {
+        remaining: usize,
+    }
+
+    impl Build for Steps {
+        fn build(&mut self) -> bool {
+            self.remaining = self.remaining.saturating_sub(1);
+            self.remaining == 0
+        }
+    }
+
+    #[test]
+    fn prepared_searches_build_first() {
+        let builds: Vec<Box<dyn Build + Send>> = vec![
+            Box::new(Steps { remaining: 3 }),
+            Box::new(Steps { remaining: 2 }),
+        ];
+        let mut search = Prepared::new(builds, Box::new(Countdown::new(0, 0)));
+        let mut stats = SolverStats::default();
+        for _ in 0..5 {
+            assert_eq!(search.expand(&mut stats), SearchStatus::Running);
+        }
+        assert_eq!(stats.expanded, 0);
+        assert_eq!(
+            search.expand(&mut stats),
+            SearchStatus::Solved(vec![0, 1, 2])
+        );
+    }
+}
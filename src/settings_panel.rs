@@ -12,13 +12,11 @@ use egui::Image;
 
 use super::MAX_WRAP;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum RunMode {
-    Dfs,
-    Bfs,
-}
-
-use crate::puzzle_panel::GameMode;
+use crate::fd::FileDialog;
+use crate::goal::Goal;
+use crate::moves::{parse_moves, Convention, Move};
+use crate::puzzle_panel::{Difficulty, GameMode};
+use crate::solver_stats::SolverStats;
 #[cfg(target_arch = "wasm32")]
 use crate::web_helpers::{isIOS, isMobile};
 
@@ -32,13 +30,39 @@ pub struct SettingsPanel {
     #[cfg_attr(feature = "serde", serde(skip))]
     n: i32,
     #[cfg_attr(feature = "serde", serde(skip))]
-    run_mode: RunMode,
+    solver: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    heuristic: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    solver_names: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    heuristic_names: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    heuristic_enabled: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    weight: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    time_budget_ms: i64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    depth_limit: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    prune_duplicates: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    weight_enabled: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    time_budget_enabled: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    depth_limit_enabled: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    prune_duplicates_enabled: bool,
     #[cfg_attr(feature = "serde", serde(skip))]
     mn_has_changed: bool,
     #[cfg_attr(feature = "serde", serde(skip))]
-    last_mn: i32,
+    last_mn: (i32, i32),
     #[cfg_attr(feature = "serde", serde(skip))]
-    mn_slider_float: f32,
+    m_slider_float: f32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    n_slider_float: f32,
     #[cfg_attr(feature = "serde", serde(skip))]
     debug_menu_open: bool,
     #[cfg_attr(feature = "serde", serde(skip))]
@@ -46,6 +70,20 @@ pub struct SettingsPanel {
     #[cfg_attr(feature = "serde", serde(skip))]
     game_mode: GameMode,
     #[cfg_attr(feature = "serde", serde(skip))]
+    difficulty: Difficulty,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    exact_moves: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    goal: Goal,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    custom_goal: Option<Goal>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    goal_file: FileDialog,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    seed_text: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_seed: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     button_ui_font_size: f32,
     #[cfg_attr(feature = "serde", serde(skip))]
     button_ui_rects: Vec<egui::Rect>,
@@ -69,6 +107,24 @@ pub struct SettingsPanel {
     puzzle_dynamic_image_count: usize,
     #[cfg_attr(feature = "serde", serde(skip))]
     selected_image_src: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_search: Option<(String, SolverStats)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    compared_search: Option<(String, SolverStats)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    puzzle_file: FileDialog,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    puzzle_text: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    save_requested: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    puzzle_file_status: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    moves_text: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    convention: Convention,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    moves: Option<Vec<Move>>,
 }
 
 impl Default for SettingsPanel {
@@ -77,13 +133,33 @@ impl Default for SettingsPanel {
             open: true,
             m: 3,
             n: 3,
-            run_mode: RunMode::Dfs,
+            solver: "A*".to_owned(),
+            heuristic: "Manhattan Distance".to_owned(),
+            solver_names: Vec::default(),
+            heuristic_names: Vec::default(),
+            heuristic_enabled: true,
+            weight: 2.,
+            time_budget_ms: 200,
+            depth_limit: 20,
+            prune_duplicates: false,
+            weight_enabled: false,
+            time_budget_enabled: false,
+            depth_limit_enabled: false,
+            prune_duplicates_enabled: false,
             mn_has_changed: false,
-            last_mn: 3,
-            mn_slider_float: 3.,
+            last_mn: (3, 3),
+            m_slider_float: 3.,
+            n_slider_float: 3.,
             debug_menu_open: false,
             puzzle_panel_constrained_width: 0.,
             game_mode: GameMode::TimeAttack,
+            difficulty: Difficulty::Easy,
+            exact_moves: 20,
+            goal: Goal::default(),
+            custom_goal: None,
+            goal_file: FileDialog::default(),
+            seed_text: String::default(),
+            last_seed: None,
             button_ui_font_size: 16.0,
             button_ui_rects: Vec::default(),
             agent_settings_label: "Agent Settings".to_owned(),
@@ -96,6 +172,15 @@ impl Default for SettingsPanel {
             gallery_dynamic_image_count: 0,
             gallery_retained_image_count: 0,
             selected_image_src: None,
+            last_search: None,
+            compared_search: None,
+            puzzle_file: FileDialog::default(),
+            puzzle_text: None,
+            save_requested: false,
+            puzzle_file_status: None,
+            moves_text: String::default(),
+            convention: Convention::default(),
+            moves: None,
         }
     }
 }
@@ -104,9 +189,20 @@ impl SettingsPanel {
     #[allow(unused)]
     pub fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         self.mn_has_changed = false;
-        if self.last_mn != self.m {
+        if self.last_mn != (self.m, self.n) {
             self.mn_has_changed = true;
-            self.last_mn = self.m;
+            self.last_mn = (self.m, self.n);
+            // a custom goal only fits the size it was written for
+            self.custom_goal = None;
+            if matches!(self.goal, Goal::Custom(_)) {
+                self.goal = Goal::default();
+            }
+        }
+        if let Some(bytes) = self.puzzle_file.get() {
+            self.puzzle_text = Some(String::from_utf8_lossy(&bytes).into_owned());
+        }
+        if let Some(bytes) = self.goal_file.get() {
+            self.load_goal(&String::from_utf8_lossy(&bytes));
         }
     }
 
@@ -122,21 +218,33 @@ impl SettingsPanel {
         ui.with_layout(egui::Layout::left_to_right(egui::Align::LEFT), |ui| {
             let slider_width = (self.puzzle_panel_constrained_width / 2.)
                 - ui.ctx().style().spacing.item_spacing.x;
-            ui.spacing_mut().slider_width = slider_width;
+            // rows and columns share the space the single size slider used to take
+            ui.spacing_mut().slider_width =
+                (slider_width / 2.) - ui.ctx().style().spacing.item_spacing.x;
             ui.add_space(
                 (ui.available_width() / 2.) - (self.puzzle_panel_constrained_width / 2.)
                     + ui.ctx().style().spacing.window_margin.left,
             );
             ui.add(
                 egui::Slider::new(
-                    &mut self.mn_slider_float,
-                    std::ops::RangeInclusive::new(2.0, 3.0),
+                    &mut self.m_slider_float,
+                    std::ops::RangeInclusive::new(2.0, 12.0),
+                )
+                .show_value(false)
+                .trailing_fill(true), //.text("== M"),
+            )
+            .on_hover_text(format!("{} rows", self.m));
+            ui.add(
+                egui::Slider::new(
+                    &mut self.n_slider_float,
+                    std::ops::RangeInclusive::new(2.0, 12.0),
                 )
                 .show_value(false)
                 .trailing_fill(true), //.text("== N"),
-            );
-            self.m = self.mn_slider_float.round() as i32;
-            self.n = self.m;
+            )
+            .on_hover_text(format!("{} columns", self.n));
+            self.m = self.m_slider_float.round() as i32;
+            self.n = self.n_slider_float.round() as i32;
 
             ui.spacing_mut().combo_width = slider_width;
 
@@ -161,6 +269,82 @@ impl SettingsPanel {
                     });
                 });
         });
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::LEFT), |ui| {
+            // seed underneath the size sliders, difficulty underneath the game mode combo box
+            let slider_width = (self.puzzle_panel_constrained_width / 2.)
+                - ui.ctx().style().spacing.item_spacing.x;
+            ui.add_space(
+                (ui.available_width() / 2.) - (self.puzzle_panel_constrained_width / 2.)
+                    + ui.ctx().style().spacing.window_margin.left,
+            );
+
+            // an empty field means every shuffle picks its own seed, shown here once it's used
+            let hint = match self.last_seed {
+                Some(seed) => format!("Seed: {}", seed),
+                None => "Random Seed".to_owned(),
+            };
+            let text_color = if self.seed_text.is_empty() || self.get_seed().is_some() {
+                None
+            } else {
+                Some(ui.visuals().error_fg_color)
+            };
+            ui.add_sized(
+                [slider_width, ui.spacing().interact_size.y],
+                egui::TextEdit::singleline(&mut self.seed_text)
+                    .hint_text(hint)
+                    .text_color_opt(text_color),
+            )
+            .on_hover_text("Fix the seed to replay (or share) the exact same shuffle");
+            ui.spacing_mut().combo_width = slider_width;
+
+            egui::ComboBox::from_id_source("difficulty")
+                .selected_text(egui::RichText::new(format!("  {}", self.difficulty)).size(18.0))
+                .show_ui(ui, |ui| {
+                    for difficulty in [
+                        Difficulty::Easy,
+                        Difficulty::Medium,
+                        Difficulty::Hard,
+                        Difficulty::Expert,
+                        Difficulty::Random,
+                        Difficulty::Exact(self.exact_moves),
+                    ] {
+                        ui.vertical_centered(|ui| {
+                            ui.selectable_value(
+                                &mut self.difficulty,
+                                difficulty,
+                                egui::RichText::new(difficulty.to_string()).size(18.0),
+                            );
+                        });
+                    }
+                })
+                .response
+                .on_hover_text("Shuffle Difficulty");
+        });
+        if let Difficulty::Exact(moves) = &mut self.difficulty {
+            let max_moves = Difficulty::max_exact_moves(self.m as usize, self.n as usize);
+            self.exact_moves = self.exact_moves.min(max_moves);
+            ui.vertical_centered(|ui| {
+                ui.add(egui::Slider::new(&mut self.exact_moves, 1..=max_moves).text("Moves"))
+                    .on_hover_text("How many moves the optimal solution of each shuffle takes");
+            });
+            *moves = self.exact_moves;
+        }
+        ui.vertical_centered(|ui| {
+            ui.spacing_mut().combo_width = self.puzzle_panel_constrained_width / 2.;
+            egui::ComboBox::from_id_source("goal")
+                .selected_text(egui::RichText::new(format!("  {}", self.goal)).size(18.0))
+                .show_ui(ui, |ui| {
+                    let goals = [Goal::RowMajor, Goal::BlankFirst, Goal::Snail];
+                    for goal in goals.into_iter().chain(self.custom_goal.clone()) {
+                        ui.vertical_centered(|ui| {
+                            let label = egui::RichText::new(goal.to_string()).size(18.0);
+                            ui.selectable_value(&mut self.goal, goal, label);
+                        });
+                    }
+                })
+                .response
+                .on_hover_text("Goal Layout");
+        });
 
         ui.separator();
         let mut start_pos = ui.cursor().left_top();
@@ -277,6 +461,65 @@ impl SettingsPanel {
                             "Dynamic Images (Gallery Panel): {}",
                             self.gallery_dynamic_image_count
                         )));
+                        if let Some((agent, stats)) = &self.last_search {
+                            ui.separator();
+                            ui.label(egui::RichText::new(format!("Last Search: {}", agent)));
+                            ui.label(egui::RichText::new(format!(
+                                "Expanded: {}, Generated: {}, Max Frontier: {}",
+                                stats.expanded, stats.generated, stats.max_frontier
+                            )));
+                            ui.label(egui::RichText::new(format!(
+                                "Peak Memory: ~{} KiB, Wall Time: {:.1} ms",
+                                stats.peak_memory_bytes / 1024,
+                                stats.wall_time_ms
+                            )));
+                            ui.label(egui::RichText::new(match stats.depth {
+                                Some(depth) => format!(
+                                    "Depth: {}, Branching Factor: {:.3}",
+                                    depth, stats.branching_factor
+                                ),
+                                None => "No Solution".to_owned(),
+                            }));
+                            if let Some((compared_agent, compared)) = &self.compared_search {
+                                egui::Grid::new("compared_search")
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        ui.label("");
+                                        ui.label(agent);
+                                        ui.label(compared_agent);
+                                        ui.end_row();
+                                        ui.label("Expanded");
+                                        ui.label(stats.expanded.to_string());
+                                        ui.label(compared.expanded.to_string());
+                                        ui.end_row();
+                                        ui.label("Generated");
+                                        ui.label(stats.generated.to_string());
+                                        ui.label(compared.generated.to_string());
+                                        ui.end_row();
+                                    });
+                            }
+                            if !stats.threads.is_empty() {
+                                egui::Grid::new("thread_stats")
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        ui.label("Thread");
+                                        ui.label("Expanded");
+                                        ui.label("Generated");
+                                        ui.label("Max Frontier");
+                                        ui.end_row();
+                                        for (thread, thread_stats) in
+                                            stats.threads.iter().enumerate()
+                                        {
+                                            ui.label(thread.to_string());
+                                            ui.label(thread_stats.expanded.to_string());
+                                            ui.label(thread_stats.generated.to_string());
+                                            ui.label(thread_stats.max_frontier.to_string());
+                                            ui.end_row();
+                                        }
+                                    });
+                            }
+                            ui.separator();
+                        }
                         if let Some(src) = &self.selected_image_src {
                             #[allow(deprecated)]
                             ui.centered(|ui| {
@@ -292,6 +535,62 @@ impl SettingsPanel {
                                 self.debug_overlay_active = !self.debug_overlay_active;
                             }
                         });
+                        #[allow(deprecated)]
+                        ui.centered(|ui| {
+                            if ui
+                                .button(egui::RichText::new("Load Puzzle File").size(16.0))
+                                .clicked()
+                            {
+                                self.puzzle_file.open();
+                            }
+                            if ui
+                                .button(egui::RichText::new("Save Puzzle File").size(16.0))
+                                .clicked()
+                            {
+                                self.save_requested = true;
+                            }
+                        });
+                        #[allow(deprecated)]
+                        ui.centered(|ui| {
+                            if ui
+                                .button(egui::RichText::new("Load Goal File").size(16.0))
+                                .clicked()
+                            {
+                                self.goal_file.open();
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.moves_text)
+                                    .hint_text("RDLLURD")
+                                    .desired_width(menu_w / 3.),
+                            )
+                            .on_hover_text("Moves to play, one of U, D, L or R each");
+                            egui::ComboBox::from_id_source("convention")
+                                .selected_text(self.convention.to_string())
+                                .show_ui(ui, |ui| {
+                                    for convention in
+                                        [Convention::BlankMoves, Convention::TileMoves]
+                                    {
+                                        ui.selectable_value(
+                                            &mut self.convention,
+                                            convention,
+                                            convention.to_string(),
+                                        );
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Whether a letter is the blank's direction or the tile's");
+                            if ui.button("Apply Moves").clicked() {
+                                match parse_moves(&self.moves_text, self.convention) {
+                                    Ok(moves) => self.moves = Some(moves),
+                                    Err(e) => self.puzzle_file_status = Some(e),
+                                }
+                            }
+                        });
+                        if let Some(status) = &self.puzzle_file_status {
+                            ui.label(egui::RichText::new(status));
+                        }
                     });
             }
 
@@ -309,8 +608,52 @@ impl SettingsPanel {
                                 "https://github.com/Stehfyn/npuzzle/blob/main/src/npuzzle.rs",
                             ));
                         });
-                        //ui.radio_value(&mut self.run_mode, RunMode::Dfs, "DFS");
-                        //ui.radio_value(&mut self.run_mode, RunMode::Bfs, "BFS");
+                        ui.label(egui::RichText::new("Search Algorithm").size(16.0));
+                        for name in &self.solver_names {
+                            ui.radio_value(&mut self.solver, name.clone(), name.as_str());
+                        }
+                        ui.separator();
+                        ui.add_enabled_ui(self.heuristic_enabled, |ui| {
+                            ui.label(egui::RichText::new("Heuristic").size(16.0));
+                            for name in &self.heuristic_names {
+                                ui.radio_value(&mut self.heuristic, name.clone(), name.as_str());
+                            }
+                        });
+                        ui.separator();
+                        ui.add_enabled_ui(self.weight_enabled, |ui| {
+                            ui.add(
+                                egui::Slider::new(&mut self.weight, 1.0..=5.0)
+                                    .step_by(0.1)
+                                    .text("Weight"),
+                            )
+                            .on_hover_text(
+                                "Solutions are at most this many times the optimal length",
+                            );
+                        });
+                        ui.add_enabled_ui(self.time_budget_enabled, |ui| {
+                            ui.add(
+                                egui::Slider::new(&mut self.time_budget_ms, 10..=2000)
+                                    .logarithmic(true)
+                                    .suffix(" ms")
+                                    .text("Time Budget"),
+                            )
+                            .on_hover_text(
+                                "How long to keep improving a solution once one is found",
+                            );
+                        });
+                        ui.add_enabled_ui(self.depth_limit_enabled, |ui| {
+                            ui.add(
+                                egui::Slider::new(&mut self.depth_limit, 1..=80)
+                                    .text("Depth Limit"),
+                            )
+                            .on_hover_text("No solution longer than this many moves is looked for");
+                        });
+                        ui.add_enabled_ui(self.prune_duplicates_enabled, |ui| {
+                            ui.checkbox(&mut self.prune_duplicates, "Prune Duplicate Paths")
+                                .on_hover_text(
+                                    "Skip move sequences that a shorter or equal one already covers",
+                                );
+                        });
                     });
             }
         });
@@ -335,8 +678,89 @@ impl SettingsPanel {
         self.game_mode
     }
 
-    pub fn get_mn(&mut self) -> i32 {
-        self.m //could just as well be self.n
+    pub fn get_difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn get_goal(&self) -> Goal {
+        self.goal.clone()
+    }
+
+    fn load_goal(&mut self, text: &str) {
+        let status = match Goal::from_text(text) {
+            Ok((m, n, goal)) if (m, n) == (self.m as usize, self.n as usize) => {
+                self.custom_goal = Some(goal.clone());
+                self.goal = goal;
+                "Goal loaded".to_owned()
+            }
+            Ok((m, n, _)) => format!(
+                "the file holds a {}x{} goal, but the grid is {}x{}",
+                m, n, self.m, self.n
+            ),
+            Err(e) => e,
+        };
+        self.puzzle_file_status = Some(status);
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed_text.trim().parse().ok()
+    }
+
+    pub fn set_last_seed(&mut self, seed: Option<u64>) {
+        self.last_seed = seed;
+    }
+
+    pub fn get_solver(&self) -> String {
+        self.solver.clone()
+    }
+
+    pub fn get_heuristic(&self) -> String {
+        self.heuristic.clone()
+    }
+
+    pub fn set_agent_options(&mut self, solver_names: Vec<String>, heuristic_names: Vec<String>) {
+        self.solver_names = solver_names;
+        self.heuristic_names = heuristic_names;
+    }
+
+    pub fn set_heuristic_enabled(&mut self, enabled: bool) {
+        self.heuristic_enabled = enabled;
+    }
+
+    pub fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn get_time_budget_ms(&self) -> i64 {
+        self.time_budget_ms
+    }
+
+    pub fn get_depth_limit(&self) -> usize {
+        self.depth_limit
+    }
+
+    pub fn get_prune_duplicates(&self) -> bool {
+        self.prune_duplicates
+    }
+
+    pub fn set_weight_enabled(&mut self, enabled: bool) {
+        self.weight_enabled = enabled;
+    }
+
+    pub fn set_time_budget_enabled(&mut self, enabled: bool) {
+        self.time_budget_enabled = enabled;
+    }
+
+    pub fn set_depth_limit_enabled(&mut self, enabled: bool) {
+        self.depth_limit_enabled = enabled;
+    }
+
+    pub fn set_prune_duplicates_enabled(&mut self, enabled: bool) {
+        self.prune_duplicates_enabled = enabled;
+    }
+
+    pub fn get_mn(&mut self) -> (i32, i32) {
+        (self.m, self.n)
     }
 
     pub fn set_gallery_retained_image_count(&mut self, count: usize) {
@@ -355,6 +779,37 @@ impl SettingsPanel {
         self.puzzle_retained_image_count = count;
     }
 
+    // The contents of a puzzle file picked in the Debug Menu, handed out once.
+    pub fn take_puzzle_text(&mut self) -> Option<String> {
+        self.puzzle_text.take()
+    }
+
+    // Moves typed into the Debug Menu, handed out once.
+    pub fn take_moves(&mut self) -> Option<Vec<Move>> {
+        self.moves.take()
+    }
+
+    // Whether Save Puzzle File was clicked since last asked.
+    pub fn take_save_request(&mut self) -> bool {
+        std::mem::take(&mut self.save_requested)
+    }
+
+    pub fn save_puzzle_text(&self, text: String) {
+        self.puzzle_file.save("puzzle.txt", text.into_bytes());
+    }
+
+    pub fn set_puzzle_file_status(&mut self, status: Option<String>) {
+        self.puzzle_file_status = status;
+    }
+
+    pub fn set_last_search(&mut self, last_search: Option<(String, SolverStats)>) {
+        self.last_search = last_search;
+    }
+
+    pub fn set_compared_search(&mut self, compared_search: Option<(String, SolverStats)>) {
+        self.compared_search = compared_search;
+    }
+
     pub fn set_puzzle_subimage_count(&mut self, count: usize) {
         self.puzzle_subimage_count = count;
     }
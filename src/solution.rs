@@ -0,0 +1,153 @@
+/**
+ * @file solution.rs
+ *
+ * @brief This is the module that checks and tidies up move lists, whether they come from a solver or
+ * were recorded while someone played.
+ *
+ * Verifying plays the moves on a copy of the board and reports the first one that would take the
+ * blank off it, or how far from the goal the moves leave it. Simplifying cuts every loop, any stretch
+ * of moves that comes back to a board already seen, immediate reversals being the shortest kind.
+ * What's left never visits the same board twice and still ends where the original did.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use crate::moves::Move;
+use crate::npuzzle::{NBoard, StateKey};
+use std::collections::HashMap;
+
+pub fn verify(board: &NBoard, moves: &[Move]) -> Result<(), String> {
+    let mut board = board.clone();
+    board.apply_moves(moves)?;
+    match board.check_win() {
+        true => Ok(()),
+        false => Err(format!(
+            "the {} moves end with {} tiles out of place",
+            moves.len(),
+            board.tiles_out()
+        )),
+    }
+}
+
+pub fn simplify(board: &NBoard, moves: &[Move]) -> Result<Vec<Move>, String> {
+    let mut board = board.clone();
+    let steps = board.indices_from_moves(moves)?;
+
+    // how many kept moves it takes to reach each board on the kept path
+    let mut seen: HashMap<StateKey, usize> = HashMap::from([(board.state_key(), 0)]);
+    let mut kept: Vec<(Move, StateKey)> = Vec::default();
+    for (mv, step) in moves.iter().zip(steps) {
+        board.swap(step);
+        let key = board.state_key();
+        match seen.get(&key) {
+            Some(length) => {
+                for (_, looped) in kept.drain(*length..) {
+                    seen.remove(&looped);
+                }
+            }
+            None => {
+                seen.insert(key.clone(), kept.len() + 1);
+                kept.push((*mv, key));
+            }
+        }
+    }
+    Ok(kept.into_iter().map(|(mv, _)| mv).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::npuzzle::GenerationMetric;
+    use crate::solver_stats::SolverStats;
+
+    // A 3x3 board and an optimal solution for it.
+    fn solved_board(seed: u64) -> (NBoard, Vec<Move>) {
+        let mut board = NBoard::new(3, 3);
+        board.generate_seeded(GenerationMetric::OptimalDepth(10, 16), seed);
+        let steps = board
+            .weighted_a_star_search(NBoard::manhattan_distance, 1., &mut SolverStats::default())
+            .unwrap();
+        let mut blank = board.get_missing_index();
+        let moves = steps
+            .into_iter()
+            .map(|step| {
+                let mv = Move::between(blank, step, 3, 3).unwrap();
+                blank = step;
+                mv
+            })
+            .collect();
+        (board, moves)
+    }
+
+    #[test]
+    fn verify_reports_the_first_illegal_move() {
+        let (board, moves) = solved_board(0);
+        assert_eq!(verify(&board, &moves), Ok(()));
+
+        // the blank starts in the top row after this many moves up
+        let row = board.get_missing_index() / 3;
+        let error = verify(&board, &vec![Move::Up; row + 2]).unwrap_err();
+        assert!(
+            error.starts_with(&format!("move {} (U)", row + 1)),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn verify_reports_an_unsolved_board() {
+        let (board, moves) = solved_board(1);
+        let error = verify(&board, &moves[..moves.len() - 1]).unwrap_err();
+        assert_eq!(
+            error,
+            format!(
+                "the {} moves end with 1 tiles out of place",
+                moves.len() - 1
+            )
+        );
+    }
+
+    #[test]
+    fn simplify_removes_reversals() {
+        let (board, moves) = solved_board(2);
+        let mut padded = Vec::default();
+        for (i, mv) in moves.iter().enumerate() {
+            padded.push(*mv);
+            if i % 3 == 0 {
+                padded.extend([mv.opposite(), *mv]);
+            }
+        }
+        assert_eq!(simplify(&board, &padded), Ok(moves));
+    }
+
+    #[test]
+    fn simplify_cuts_loops() {
+        for seed in 0..5 {
+            let (board, moves) = solved_board(seed);
+            // three laps around a 2x2 block bring its tiles back where they were
+            let blank = board.get_missing_index();
+            let across = if blank % 3 < 2 {
+                Move::Right
+            } else {
+                Move::Left
+            };
+            let down = if blank / 3 < 2 { Move::Down } else { Move::Up };
+            let lap = [across, down, across.opposite(), down.opposite()];
+            let mut looped: Vec<Move> = lap.iter().cycle().take(12).copied().collect();
+            looped.extend(&moves);
+            assert_eq!(verify(&board, &looped), Ok(()));
+
+            let simplified = simplify(&board, &looped).unwrap();
+            assert_eq!(simplified, moves, "seed {}", seed);
+            assert_eq!(verify(&board, &simplified), Ok(()));
+        }
+    }
+
+    #[test]
+    fn simplify_rejects_illegal_moves() {
+        let (board, _) = solved_board(3);
+        let row = board.get_missing_index() / 3;
+        assert!(simplify(&board, &vec![Move::Up; row + 1]).is_err());
+    }
+}
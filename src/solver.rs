@@ -0,0 +1,854 @@
+/**
+ * @file solver.rs
+ *
+ * @brief This is the module that puts NBoard's search algorithms and heuristics behind the Solver and
+ * Heuristic traits, and collects them in a Registry the Agent Settings window can choose from.
+ *
+ * New algorithms or heuristics only need to implement one of the traits and be registered; neither
+ * npuzzle.rs nor the ui has to know about them. Solvers count their own work into a SolverStats,
+ * and those that can be run a slice at a time hand the Registry a search to wrap in a SolverHandle.
+ * The rest are wrapped whole, so they still only start running once the handle is stepped.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use crate::distance_table::DistanceTable;
+use crate::move_pruning::{DUPLICATE_PATH_LENGTH, INVERSE_LENGTH};
+use crate::npuzzle::{
+    AStarSearch, AraStarSearch, BidirectionalSearch, DepthFirstSearch, IdaStarSearch, NBoard,
+};
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+use crate::parallel_a_star::ParallelSearch;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::pattern_database::PARTITION_6_6_3;
+use crate::pattern_database::{PatternDatabase, PARTITION_4_4, PARTITION_5_5_5};
+use crate::search::{run_to_completion, Build, Expand, Prepared, SearchStatus, SolverHandle};
+use crate::solver_stats::SolverStats;
+use std::sync::Arc;
+
+pub trait Heuristic: Send + Sync {
+    fn name(&self) -> &str;
+    fn estimate(&self, board: &NBoard) -> usize;
+
+    // Tables the estimate relies on that still have to be built. They are built a step at a time
+    // before the search starts, rather than all at once by its first estimate.
+    fn prepare(&self, _board: &NBoard) -> Option<Box<dyn Build + Send>> {
+        None
+    }
+}
+
+pub trait Solver: Send + Sync {
+    fn name(&self) -> &str;
+    fn solve(
+        &self,
+        board: &NBoard,
+        heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>>;
+
+    // A search that can be advanced a slice at a time. Solvers without one are run to completion
+    // the first time their handle is stepped.
+    fn search(
+        &self,
+        _board: &NBoard,
+        _heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        None
+    }
+
+    // Like Heuristic::prepare, for tables the solver itself looks up.
+    fn prepare(&self, _board: &NBoard) -> Option<Box<dyn Build + Send>> {
+        None
+    }
+
+    // Uninformed searches ignore whichever heuristic is picked.
+    fn uses_heuristic(&self) -> bool {
+        true
+    }
+
+    fn uses_weight(&self) -> bool {
+        false
+    }
+
+    fn uses_time_budget(&self) -> bool {
+        false
+    }
+
+    fn uses_depth_limit(&self) -> bool {
+        false
+    }
+
+    // Depth-first searches can cut duplicate paths as well as moves straight back.
+    fn uses_move_pruning(&self) -> bool {
+        false
+    }
+
+    // The solver this one is run against on the same board, so the stats can show what it saved.
+    fn compared_with(&self) -> Option<&str> {
+        None
+    }
+}
+
+pub struct ManhattanDistance;
+
+impl Heuristic for ManhattanDistance {
+    fn name(&self) -> &str {
+        "Manhattan Distance"
+    }
+
+    fn estimate(&self, board: &NBoard) -> usize {
+        board.manhattan_distance()
+    }
+}
+
+pub struct LinearConflict;
+
+impl Heuristic for LinearConflict {
+    fn name(&self) -> &str {
+        "Linear Conflict"
+    }
+
+    fn estimate(&self, board: &NBoard) -> usize {
+        board.linear_conflict()
+    }
+}
+
+pub struct WalkingDistance;
+
+impl Heuristic for WalkingDistance {
+    fn name(&self) -> &str {
+        "Walking Distance"
+    }
+
+    fn estimate(&self, board: &NBoard) -> usize {
+        board.walking_distance()
+    }
+}
+
+// Additive pattern databases split `sizes` ways on 4x4 boards and 4-4 on 3x3, built the first time
+// each blank is estimated. Other board sizes fall back to the Manhattan distance.
+pub struct PatternDatabases {
+    pub name: &'static str,
+    pub sizes: &'static [usize],
+}
+
+impl Heuristic for PatternDatabases {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn estimate(&self, board: &NBoard) -> usize {
+        let Some((sizes, blank_tile)) = self.partition(board) else {
+            return board.manhattan_distance();
+        };
+        match PatternDatabase::ready(board.get_m(), board.get_n(), blank_tile, sizes) {
+            Some(database) => database.distance(board),
+            None => board.manhattan_distance(),
+        }
+    }
+
+    fn prepare(&self, board: &NBoard) -> Option<Box<dyn Build + Send>> {
+        let (sizes, blank_tile) = self.partition(board)?;
+        let builder = PatternDatabase::builder(board.get_m(), board.get_n(), blank_tile, sizes)?;
+        Some(Box::new(builder))
+    }
+}
+
+impl PatternDatabases {
+    // The partition a board is looked up in, and its blank's goal cell.
+    fn partition(&self, board: &NBoard) -> Option<(&'static [usize], usize)> {
+        let sizes: &'static [usize] = match (board.get_m(), board.get_n()) {
+            (3, 3) => &PARTITION_4_4,
+            (4, 4) => self.sizes,
+            _ => return None,
+        };
+        Some((sizes, board.blank_goal()?))
+    }
+}
+
+pub struct AStar;
+
+impl Solver for AStar {
+    fn name(&self) -> &str {
+        "A*"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        board.weighted_a_star_search(|b| heuristic.estimate(b), 1., stats)
+    }
+
+    fn search(
+        &self,
+        board: &NBoard,
+        heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        Some(Box::new(AStarSearch::new(
+            board,
+            move |b: &NBoard| heuristic.estimate(b),
+            1.,
+        )))
+    }
+}
+
+// A* spread over every core, for native builds with the "parallel" feature.
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+pub struct ParallelAStar {
+    pub threads: usize,
+}
+
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+impl Default for ParallelAStar {
+    fn default() -> Self {
+        Self {
+            threads: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        }
+    }
+}
+
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+impl Solver for ParallelAStar {
+    fn name(&self) -> &str {
+        "Parallel A*"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        board.parallel_a_star_search(|b| heuristic.estimate(b), self.threads, stats)
+    }
+
+    fn search(
+        &self,
+        board: &NBoard,
+        heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        Some(Box::new(ParallelSearch::new(
+            board,
+            move |b: &NBoard| heuristic.estimate(b),
+            self.threads,
+        )))
+    }
+}
+
+// Searches from the scramble and the goal at once until the two meet in the middle.
+pub struct BidirectionalAStar;
+
+impl Solver for BidirectionalAStar {
+    fn name(&self) -> &str {
+        "Bidirectional A*"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        board.bidirectional_search(|b| heuristic.estimate(b), stats)
+    }
+
+    fn search(
+        &self,
+        board: &NBoard,
+        heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        Some(Box::new(BidirectionalSearch::new(
+            board,
+            move |b: &NBoard| heuristic.estimate(b),
+        )))
+    }
+
+    fn compared_with(&self) -> Option<&str> {
+        Some("A*")
+    }
+}
+
+// Two breadth-first searches half as deep generate far fewer states than one all the way.
+pub struct BidirectionalBreadthFirst;
+
+impl Solver for BidirectionalBreadthFirst {
+    fn name(&self) -> &str {
+        "Bidirectional BFS"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        _heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        board.bidirectional_search(|_| 0, stats)
+    }
+
+    fn search(
+        &self,
+        board: &NBoard,
+        _heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        Some(Box::new(BidirectionalSearch::new(board, |_: &NBoard| 0)))
+    }
+
+    fn uses_heuristic(&self) -> bool {
+        false
+    }
+
+    fn compared_with(&self) -> Option<&str> {
+        Some("BFS")
+    }
+}
+
+// Trades optimality for speed: solutions are at most `weight` times longer than the shortest.
+pub struct WeightedAStar {
+    pub weight: f64,
+}
+
+impl Default for WeightedAStar {
+    fn default() -> Self {
+        Self { weight: 2. }
+    }
+}
+
+impl Solver for WeightedAStar {
+    fn name(&self) -> &str {
+        "Weighted A*"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        board.weighted_a_star_search(|b| heuristic.estimate(b), self.weight, stats)
+    }
+
+    fn search(
+        &self,
+        board: &NBoard,
+        heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        Some(Box::new(AStarSearch::new(
+            board,
+            move |b: &NBoard| heuristic.estimate(b),
+            self.weight,
+        )))
+    }
+
+    fn uses_weight(&self) -> bool {
+        true
+    }
+}
+
+// Starts out like Weighted A* and keeps tightening the solution until the time budget runs out.
+// The first solution is always waited for, even past the budget.
+pub struct AraStar {
+    pub weight: f64,
+    pub budget_ms: i64,
+}
+
+impl Default for AraStar {
+    fn default() -> Self {
+        Self {
+            weight: 2.,
+            budget_ms: 200,
+        }
+    }
+}
+
+impl Solver for AraStar {
+    fn name(&self) -> &str {
+        "ARA*"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        board
+            .ara_star_search(
+                |b| heuristic.estimate(b),
+                self.weight,
+                self.budget_ms,
+                stats,
+            )
+            .pop()
+            .map(|solution| solution.steps)
+    }
+
+    fn search(
+        &self,
+        board: &NBoard,
+        heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        Some(Box::new(AraStarSearch::new(
+            board,
+            move |b: &NBoard| heuristic.estimate(b),
+            self.weight,
+            self.budget_ms,
+        )))
+    }
+
+    fn uses_weight(&self) -> bool {
+        true
+    }
+
+    fn uses_time_budget(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Default)]
+pub struct IdaStar {
+    pub prune_duplicates: bool,
+}
+
+impl Solver for IdaStar {
+    fn name(&self) -> &str {
+        "IDA*"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        let search = IdaStarSearch::new(board, |b: &NBoard| heuristic.estimate(b), usize::MAX);
+        run_to_completion(
+            &mut search.with_pruner(pruner_length(self.prune_duplicates)),
+            stats,
+        )
+    }
+
+    fn search(
+        &self,
+        board: &NBoard,
+        heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        let search = IdaStarSearch::new(board, move |b: &NBoard| heuristic.estimate(b), usize::MAX);
+        Some(Box::new(
+            search.with_pruner(pruner_length(self.prune_duplicates)),
+        ))
+    }
+
+    fn uses_move_pruning(&self) -> bool {
+        true
+    }
+}
+
+// With every move costing the same, A* without an estimate expands states in breadth-first order.
+pub struct BreadthFirst;
+
+impl Solver for BreadthFirst {
+    fn name(&self) -> &str {
+        "BFS"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        _heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        board.weighted_a_star_search(|_| 0, 1., stats)
+    }
+
+    fn search(
+        &self,
+        board: &NBoard,
+        _heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        Some(Box::new(AStarSearch::new(board, |_: &NBoard| 0, 1.)))
+    }
+
+    fn uses_heuristic(&self) -> bool {
+        false
+    }
+}
+
+// The first solution found within `depth_limit` moves, which is rarely the shortest.
+pub struct DepthLimited {
+    pub depth_limit: usize,
+    pub prune_duplicates: bool,
+}
+
+impl Default for DepthLimited {
+    fn default() -> Self {
+        Self {
+            depth_limit: 20,
+            prune_duplicates: false,
+        }
+    }
+}
+
+impl Solver for DepthLimited {
+    fn name(&self) -> &str {
+        "DLS"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        _heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        let search = DepthFirstSearch::depth_limited(board, self.depth_limit);
+        run_to_completion(
+            &mut search.with_pruner(pruner_length(self.prune_duplicates)),
+            stats,
+        )
+    }
+
+    fn search(
+        &self,
+        board: &NBoard,
+        _heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        let search = DepthFirstSearch::depth_limited(board, self.depth_limit);
+        Some(Box::new(
+            search.with_pruner(pruner_length(self.prune_duplicates)),
+        ))
+    }
+
+    fn uses_heuristic(&self) -> bool {
+        false
+    }
+
+    fn uses_depth_limit(&self) -> bool {
+        true
+    }
+
+    fn uses_move_pruning(&self) -> bool {
+        true
+    }
+}
+
+// Depth limited searches one move deeper at a time, so the first solution is a shortest one.
+pub struct IterativeDeepening {
+    pub depth_limit: usize,
+    pub prune_duplicates: bool,
+}
+
+impl Default for IterativeDeepening {
+    fn default() -> Self {
+        Self {
+            depth_limit: 20,
+            prune_duplicates: false,
+        }
+    }
+}
+
+impl Solver for IterativeDeepening {
+    fn name(&self) -> &str {
+        "IDDFS"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        _heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        let search = DepthFirstSearch::iterative_deepening(board, self.depth_limit);
+        run_to_completion(
+            &mut search.with_pruner(pruner_length(self.prune_duplicates)),
+            stats,
+        )
+    }
+
+    fn search(
+        &self,
+        board: &NBoard,
+        _heuristic: Arc<dyn Heuristic>,
+    ) -> Option<Box<dyn Expand + Send>> {
+        let search = DepthFirstSearch::iterative_deepening(board, self.depth_limit);
+        Some(Box::new(
+            search.with_pruner(pruner_length(self.prune_duplicates)),
+        ))
+    }
+
+    fn uses_heuristic(&self) -> bool {
+        false
+    }
+
+    fn uses_depth_limit(&self) -> bool {
+        true
+    }
+
+    fn uses_move_pruning(&self) -> bool {
+        true
+    }
+}
+
+fn pruner_length(prune_duplicates: bool) -> usize {
+    match prune_duplicates {
+        true => DUPLICATE_PATH_LENGTH,
+        false => INVERSE_LENGTH,
+    }
+}
+
+// Row by row and column by column like a person would, for boards too big to search.
+pub struct Layered;
+
+impl Solver for Layered {
+    fn name(&self) -> &str {
+        "Layered"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        _heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        board.layered_search(stats)
+    }
+
+    fn uses_heuristic(&self) -> bool {
+        false
+    }
+}
+
+// Exact distances looked up rather than searched for, on boards of at most 9 cells.
+pub struct TableLookup;
+
+impl Solver for TableLookup {
+    fn name(&self) -> &str {
+        "Distance Table"
+    }
+
+    fn solve(
+        &self,
+        board: &NBoard,
+        _heuristic: &dyn Heuristic,
+        stats: &mut SolverStats,
+    ) -> Option<Vec<usize>> {
+        board.distance_table_search(stats)
+    }
+
+    fn prepare(&self, board: &NBoard) -> Option<Box<dyn Build + Send>> {
+        let builder = DistanceTable::builder(board.get_m(), board.get_n(), board.blank_goal()?)?;
+        Some(Box::new(builder))
+    }
+
+    fn uses_heuristic(&self) -> bool {
+        false
+    }
+}
+
+// A whole solve as a single unit of work.
+struct Unsliced {
+    solver: Arc<dyn Solver>,
+    board: NBoard,
+    heuristic: Arc<dyn Heuristic>,
+}
+
+impl Expand for Unsliced {
+    fn expand(&mut self, stats: &mut SolverStats) -> SearchStatus {
+        match self
+            .solver
+            .solve(&self.board, self.heuristic.as_ref(), stats)
+        {
+            Some(steps) => SearchStatus::Solved(steps),
+            None => SearchStatus::Failed,
+        }
+    }
+}
+
+pub struct Registry {
+    solvers: Vec<Arc<dyn Solver>>,
+    heuristics: Vec<Arc<dyn Heuristic>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self {
+            solvers: Vec::default(),
+            heuristics: Vec::default(),
+        };
+        registry.register_solver(Box::new(AStar));
+        #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+        registry.register_solver(Box::new(ParallelAStar::default()));
+        registry.register_solver(Box::new(BidirectionalAStar));
+        registry.register_solver(Box::new(WeightedAStar::default()));
+        registry.register_solver(Box::new(AraStar::default()));
+        registry.register_solver(Box::new(IdaStar::default()));
+        registry.register_solver(Box::new(BreadthFirst));
+        registry.register_solver(Box::new(BidirectionalBreadthFirst));
+        registry.register_solver(Box::new(DepthLimited::default()));
+        registry.register_solver(Box::new(IterativeDeepening::default()));
+        registry.register_solver(Box::new(Layered));
+        registry.register_solver(Box::new(TableLookup));
+        registry.register_heuristic(Box::new(ManhattanDistance));
+        registry.register_heuristic(Box::new(LinearConflict));
+        registry.register_heuristic(Box::new(WalkingDistance));
+        registry.register_heuristic(Box::new(PatternDatabases {
+            name: "Pattern Database (5-5-5)",
+            sizes: &PARTITION_5_5_5,
+        }));
+        // too slow to build in a browser tab
+        #[cfg(not(target_arch = "wasm32"))]
+        registry.register_heuristic(Box::new(PatternDatabases {
+            name: "Pattern Database (6-6-3)",
+            sizes: &PARTITION_6_6_3,
+        }));
+        registry
+    }
+}
+
+impl Registry {
+    // Registering under a name that is already taken replaces the earlier entry in place, so
+    // re-registering a solver with new parameters doesn't reorder the menu.
+    pub fn register_solver(&mut self, solver: Box<dyn Solver>) {
+        match self.solvers.iter().position(|s| s.name() == solver.name()) {
+            Some(i) => self.solvers[i] = Arc::from(solver),
+            None => self.solvers.push(Arc::from(solver)),
+        }
+    }
+
+    pub fn register_heuristic(&mut self, heuristic: Box<dyn Heuristic>) {
+        self.heuristics.retain(|h| h.name() != heuristic.name());
+        self.heuristics.push(Arc::from(heuristic));
+    }
+
+    pub fn solver_names(&self) -> Vec<String> {
+        self.solvers.iter().map(|s| s.name().to_owned()).collect()
+    }
+
+    pub fn heuristic_names(&self) -> Vec<String> {
+        self.heuristics
+            .iter()
+            .map(|h| h.name().to_owned())
+            .collect()
+    }
+
+    pub fn solver(&self, name: &str) -> Option<&dyn Solver> {
+        self.solvers
+            .iter()
+            .find(|s| s.name() == name)
+            .map(|s| s.as_ref())
+    }
+
+    pub fn compared_with(&self, solver: &str) -> Option<&str> {
+        self.solver(solver)
+            .and_then(|solver| solver.compared_with())
+    }
+
+    #[allow(unused)]
+    pub fn heuristic(&self, name: &str) -> Option<&dyn Heuristic> {
+        self.heuristics
+            .iter()
+            .find(|h| h.name() == name)
+            .map(|h| h.as_ref())
+    }
+
+    #[allow(unused)]
+    pub fn solve(
+        &self,
+        board: &NBoard,
+        solver: &str,
+        heuristic: &str,
+    ) -> (Option<Vec<usize>>, SolverStats) {
+        let mut handle = self.start(board, solver, heuristic);
+        handle.run();
+        (handle.get_solution(), handle.get_stats())
+    }
+
+    // Unknown heuristics fall back to the Manhattan distance.
+    pub fn start(&self, board: &NBoard, solver: &str, heuristic: &str) -> SolverHandle {
+        let heuristic: Arc<dyn Heuristic> =
+            match self.heuristics.iter().find(|h| h.name() == heuristic) {
+                Some(heuristic) => heuristic.clone(),
+                None => Arc::new(ManhattanDistance),
+            };
+        let Some(solver) = self.solvers.iter().find(|s| s.name() == solver) else {
+            return SolverHandle::finished(None, SolverStats::default());
+        };
+        let mut builds: Vec<Box<dyn Build + Send>> = solver.prepare(board).into_iter().collect();
+        if solver.uses_heuristic() {
+            builds.extend(heuristic.prepare(board));
+        }
+        let search = match solver.search(board, heuristic.clone()) {
+            Some(search) => search,
+            None => Box::new(Unsliced {
+                solver: solver.clone(),
+                board: board.clone(),
+                heuristic,
+            }),
+        };
+        match builds.is_empty() {
+            true => SolverHandle::new(search),
+            false => SolverHandle::new(Box::new(Prepared::new(builds, search))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::npuzzle::GenerationMetric;
+
+    #[test]
+    fn every_solver_reports_its_work() {
+        let registry = Registry::default();
+        let mut board = NBoard::new(3, 3);
+        board.generate_seeded(GenerationMetric::OptimalDepth(14, 14), 5);
+        for solver in registry.solver_names() {
+            let (steps, stats) = registry.solve(&board, &solver, "Manhattan Distance");
+            let steps = steps.unwrap_or_else(|| panic!("{} found no solution", solver));
+            assert_eq!(stats.depth, Some(steps.len()), "{}", solver);
+            assert!(stats.expanded > 0, "{}", solver);
+            assert!(stats.generated >= stats.expanded, "{}", solver);
+            assert!(stats.max_frontier > 0, "{}", solver);
+            assert!(stats.peak_memory_bytes > 0, "{}", solver);
+            assert!(stats.wall_time_ms >= 0., "{}", solver);
+            assert!(stats.branching_factor >= 1., "{}", solver);
+        }
+    }
+
+    #[test]
+    fn better_heuristics_expand_less() {
+        let registry = Registry::default();
+        let mut board = NBoard::new(4, 4);
+        board.generate_seeded(GenerationMetric::Random(60), 9);
+        let expanded = |heuristic: &str| registry.solve(&board, "IDA*", heuristic).1.expanded;
+        assert!(expanded("Linear Conflict") <= expanded("Manhattan Distance"));
+        assert!(expanded("Walking Distance") <= expanded("Manhattan Distance"));
+    }
+
+    #[test]
+    fn unknown_solver_does_no_work() {
+        let registry = Registry::default();
+        let (steps, stats) = registry.solve(&NBoard::new(3, 3), "Nope", "Manhattan Distance");
+        assert_eq!(steps, None);
+        assert_eq!(stats, SolverStats::default());
+    }
+
+    #[test]
+    fn compared_solvers_find_as_short_a_solution() {
+        let registry = Registry::default();
+        let board = {
+            let mut board = NBoard::new(3, 3);
+            board.generate_seeded(GenerationMetric::Random(60), 3);
+            board
+        };
+        for solver in ["Bidirectional A*", "Bidirectional BFS"] {
+            let compared = registry.compared_with(solver).unwrap();
+            let (steps, _) = registry.solve(&board, solver, "Manhattan Distance");
+            let (compared_steps, _) = registry.solve(&board, compared, "Manhattan Distance");
+            assert!(steps.is_some(), "{}", solver);
+            assert_eq!(steps.map(|s| s.len()), compared_steps.map(|s| s.len()));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+/**
+ * @file solver_stats.rs
+ *
+ * @brief This is the module that records how much work a search did, so heuristics and algorithms
+ * can be compared on the same board.
+ *
+ * NBoard's searches count expansions, generations and the frontier as they go; whoever runs the
+ * search times it and fills in the solution's depth and effective branching factor at the end.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolverStats {
+    // states whose successors were generated
+    pub expanded: usize,
+    // successors created, duplicates included
+    pub generated: usize,
+    // open list for best-first searches, path length for depth-first ones
+    pub max_frontier: usize,
+    // rough high-water mark of the boards, paths and keys the search held at once
+    pub peak_memory_bytes: usize,
+    pub wall_time_ms: f64,
+    // None when no solution was found
+    pub depth: Option<usize>,
+    pub branching_factor: f64,
+    // each thread's share of the work, for searches spread over several
+    pub threads: Vec<SolverStats>,
+}
+
+impl SolverStats {
+    pub fn record_frontier(&mut self, frontier: usize, memory_bytes: usize) {
+        self.max_frontier = self.max_frontier.max(frontier);
+        self.peak_memory_bytes = self.peak_memory_bytes.max(memory_bytes);
+    }
+
+    pub fn finish(&mut self, elapsed: chrono::Duration, steps: Option<&Vec<usize>>) {
+        self.wall_time_ms = elapsed.num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.;
+        self.depth = steps.map(|steps| steps.len());
+        self.branching_factor = match self.depth {
+            Some(depth) => effective_branching_factor(self.generated, depth),
+            None => 0.,
+        };
+    }
+}
+
+impl std::fmt::Display for SolverStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expanded {}, generated {}, max frontier {}, ~{} KiB, {:.1} ms, ",
+            self.expanded,
+            self.generated,
+            self.max_frontier,
+            self.peak_memory_bytes / 1024,
+            self.wall_time_ms
+        )?;
+        match self.depth {
+            Some(depth) => write!(f, "depth {}, b* {:.3}", depth, self.branching_factor),
+            None => write!(f, "no solution"),
+        }
+    }
+}
+
+// The b* for which a uniform tree of the solution's depth holds as many nodes as were generated:
+// generated + 1 = 1 + b* + b*^2 + ... + b*^depth.
+pub fn effective_branching_factor(generated: usize, depth: usize) -> f64 {
+    if depth == 0 {
+        return 0.;
+    }
+    let total = (generated + 1) as f64;
+    let tree_size = |b: f64| (0..=depth).map(|i| b.powi(i as i32)).sum::<f64>();
+    if tree_size(1.) >= total {
+        return 1.;
+    }
+
+    let (mut low, mut high) = (1., total);
+    for _ in 0..64 {
+        let mid = (low + high) / 2.;
+        if tree_size(mid) < total {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branching_factor_fits_the_generated_count() {
+        // 1 + 2 + 4 + 8 nodes in a binary tree three deep
+        assert!((effective_branching_factor(14, 3) - 2.).abs() < 1e-6);
+        assert_eq!(effective_branching_factor(2, 3), 1.);
+        assert_eq!(effective_branching_factor(10, 0), 0.);
+    }
+
+    #[test]
+    fn finish_fills_in_depth_and_branching_factor() {
+        let mut stats = SolverStats {
+            generated: 14,
+            ..Default::default()
+        };
+        stats.finish(chrono::Duration::microseconds(1500), Some(&vec![0, 1, 2]));
+        assert_eq!(stats.depth, Some(3));
+        assert_eq!(stats.wall_time_ms, 1.5);
+        assert!((stats.branching_factor - 2.).abs() < 1e-6);
+
+        stats.finish(chrono::Duration::zero(), None);
+        assert_eq!(stats.depth, None);
+        assert_eq!(stats.branching_factor, 0.);
+        assert!(stats.to_string().ends_with("no solution"));
+    }
+}
@@ -0,0 +1,108 @@
+/**
+ * @file walking_distance.rs
+ *
+ * @brief This is the module that builds and caches the lookup tables behind Ken'ichiro Takahashi's
+ * walking distance heuristic, consumed by NBoard::walking_distance in npuzzle.rs.
+ *
+ * A table describes one axis of the board. Each line (row or column) is summarized by how many of
+ * its tiles belong to every goal line, and the only way to change that summary is for the blank to
+ * trade places with a tile from a neighbouring line. A breadth-first search from the goal summary
+ * gives the exact number of such trades, which is a lower bound on the moves along that axis.
+ *
+ * @author Stephen Foster
+ * Contact: stephenfoster@nevada.unr.edu
+ *
+ */
+use log::debug;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+// Past 4 lines of 4 tiles the table grows too large to build on the fly.
+const MAX_LINES: usize = 4;
+const MAX_WIDTH: usize = 4;
+const COUNT_BITS: usize = 3;
+
+type TableKey = (usize, usize, usize);
+
+static TABLES: Mutex<Option<HashMap<TableKey, Arc<WalkingDistanceTable>>>> = Mutex::new(None);
+
+pub struct WalkingDistanceTable {
+    distances: HashMap<u64, u8>,
+}
+
+impl WalkingDistanceTable {
+    pub fn supports(lines: usize, width: usize) -> bool {
+        lines <= MAX_LINES && width <= MAX_WIDTH
+    }
+
+    // Tables are shared by every board with the same shape and blank goal line, so they are built
+    // once per process and handed out behind an Arc.
+    pub fn get(lines: usize, width: usize, blank_goal_line: usize) -> Arc<Self> {
+        let mut tables = TABLES.lock().unwrap();
+        tables
+            .get_or_insert_with(HashMap::new)
+            .entry((lines, width, blank_goal_line))
+            .or_insert_with(|| Arc::new(Self::build(lines, width, blank_goal_line)))
+            .clone()
+    }
+
+    // `counts[line * lines + goal_line]` holds how many tiles sitting in `line` belong in
+    // `goal_line`, the blank excluded.
+    pub fn distance(&self, counts: &[u8], blank_line: usize) -> usize {
+        match self.distances.get(&encode(counts, blank_line)) {
+            Some(distance) => *distance as usize,
+            None => 0,
+        }
+    }
+
+    fn build(lines: usize, width: usize, blank_goal_line: usize) -> Self {
+        let mut goal = vec![0u8; lines * lines];
+        for line in 0..lines {
+            goal[line * lines + line] = width as u8;
+        }
+        goal[blank_goal_line * lines + blank_goal_line] -= 1;
+
+        let mut distances = HashMap::new();
+        let mut queue = VecDeque::new();
+        distances.insert(encode(&goal, blank_goal_line), 0u8);
+        queue.push_back((goal, blank_goal_line, 0u8));
+
+        while let Some((counts, blank_line, distance)) = queue.pop_front() {
+            for next_line in [blank_line.wrapping_sub(1), blank_line + 1] {
+                if next_line >= lines {
+                    continue;
+                }
+                for goal_line in 0..lines {
+                    if counts[next_line * lines + goal_line] == 0 {
+                        continue;
+                    }
+                    // a tile from the neighbouring line steps into the blank's line
+                    let mut next = counts.clone();
+                    next[next_line * lines + goal_line] -= 1;
+                    next[blank_line * lines + goal_line] += 1;
+
+                    if let Entry::Vacant(entry) = distances.entry(encode(&next, next_line)) {
+                        entry.insert(distance + 1);
+                        queue.push_back((next, next_line, distance + 1));
+                    }
+                }
+            }
+        }
+
+        debug!(
+            "walking distance: built {}x{} table (blank line {}) with {} states",
+            lines,
+            width,
+            blank_goal_line,
+            distances.len()
+        );
+        Self { distances }
+    }
+}
+
+fn encode(counts: &[u8], blank_line: usize) -> u64 {
+    counts.iter().fold(blank_line as u64, |key, count| {
+        (key << COUNT_BITS) | *count as u64
+    })
+}